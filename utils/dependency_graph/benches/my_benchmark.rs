@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use dependency_graph::DependencyGraph;
+use dependency_graph::{ArenaGraph, DependencyGraph};
 use std::time::Duration;
 // Import your DepGraph code here
 
@@ -16,6 +16,15 @@ fn bench_add_node(c: &mut Criterion) {
         });
     });
 
+    group.bench_function("add 1000 nodes with capacity", |b| {
+        b.iter(|| {
+            let mut graph = DependencyGraph::with_capacity(1000);
+            for i in 0..1000 {
+                graph.get_or_add_node(black_box(i));
+            }
+        });
+    });
+
     group.finish();
 }
 
@@ -33,6 +42,48 @@ fn bench_add_edge(c: &mut Criterion) {
         });
     });
 
+    group.bench_function("add 1000 edges unchecked", |b| {
+        b.iter(|| {
+            let mut graph = DependencyGraph::new();
+            let nodes: Vec<_> = (0..1000).map(|i| graph.get_or_add_node(i)).collect();
+            for i in 0..999 {
+                // SAFETY: the edges form a chain, which is acyclic.
+                unsafe { DependencyGraph::add_edge_unchecked(&nodes[i], &nodes[i + 1]) };
+            }
+        });
+    });
+
+    // A chain wired from the top down, each new edge lands below the whole ancestry built so far.
+    group.bench_function("add 10000 edges top-down", |b| {
+        b.iter(|| {
+            let mut graph = DependencyGraph::new();
+            let nodes: Vec<_> = (0..10000).map(|i| graph.get_or_add_node(i)).collect();
+            for i in 0..9999 {
+                DependencyGraph::add_edge(&nodes[i], &nodes[i + 1]).unwrap();
+            }
+        });
+    });
+
+    group.bench_function("add 10000 edges bottom-up", |b| {
+        b.iter(|| {
+            let mut graph = DependencyGraph::new();
+            let nodes: Vec<_> = (0..10000).map(|i| graph.get_or_add_node(i)).collect();
+            for i in (0..9999).rev() {
+                DependencyGraph::add_edge(&nodes[i], &nodes[i + 1]).unwrap();
+            }
+        });
+    });
+
+    group.bench_function("add 1000 edges in arena", |b| {
+        b.iter(|| {
+            let mut graph = ArenaGraph::new();
+            let nodes: Vec<_> = (0..1000).map(|i| graph.get_or_add_node(i)).collect();
+            for i in 0..999 {
+                graph.add_edge(nodes[i], nodes[i + 1]).unwrap();
+            }
+        });
+    });
+
     group.finish();
 }
 
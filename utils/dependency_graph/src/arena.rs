@@ -0,0 +1,292 @@
+use crate::collections::{HashState, Map};
+use crate::AddEdgeError;
+use crate::AddEdgeError::{CyclicRelation, SameNode};
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Display;
+use core::hash::{BuildHasher, Hash};
+
+/// A handle to a node of an `ArenaGraph`.
+///
+/// It is only meaningful for the graph that created it: using it with another graph
+/// refers to an unrelated node or panics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    /// The position of the node in the graph, in insertion order.
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+pub(crate) struct NodeData<T> {
+    value: T,
+    childs: Vec<NodeId>,
+    parents: Vec<NodeId>,
+}
+
+/// A dependency graph storing its nodes in a single arena.
+///
+/// `ArenaGraph<T>` offers the same model as `DependencyGraph<T>`, but nodes are kept in a `Vec`
+/// and edges are stored as indices in it. Nodes are referred to by `NodeId` handles instead of
+/// `Rc<RefCell<..>>`, which removes the reference counting and the runtime borrow checks, and
+/// makes the graph `Send` and `Sync` when `T` is.
+pub struct ArenaGraph<T> {
+    nodes: Vec<NodeData<T>>,
+    // Node ids bucketed by the hash of their value, so existing values are found quickly.
+    index: Map<u64, Vec<NodeId>>,
+    hash_builder: HashState,
+}
+
+impl<T> ArenaGraph<T> {
+    /// Creates a new, empty `ArenaGraph<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::ArenaGraph;
+    /// let graph: ArenaGraph<i32> = ArenaGraph::new();
+    /// ```
+    pub fn new() -> ArenaGraph<T> {
+        ArenaGraph {
+            nodes: Vec::new(),
+            index: Map::new(),
+            hash_builder: HashState::default(),
+        }
+    }
+
+    /// Retrieves the id of the node with the given value, adding the node if it doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to search for or add to the graph.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement both the `Hash` and `Eq` traits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::ArenaGraph;
+    /// let mut graph = ArenaGraph::new();
+    ///
+    /// let node = graph.get_or_add_node(42);
+    /// assert_eq!(graph.get_or_add_node(42), node);
+    /// ```
+    pub fn get_or_add_node(&mut self, value: T) -> NodeId
+    where
+        T: Hash + Eq,
+    {
+        let hash = self.hash_builder.hash_one(&value);
+        let bucket = self.index.entry(hash).or_default();
+
+        if let Some(&id) = bucket.iter().find(|id| self.nodes[id.0].value == value) {
+            return id;
+        }
+
+        let id = NodeId(self.nodes.len());
+        bucket.push(id);
+        self.nodes.push(NodeData {
+            value,
+            childs: Vec::new(),
+            parents: Vec::new(),
+        });
+
+        id
+    }
+
+    /// Adds an edge between two nodes in the graph.
+    ///
+    /// Adding an edge that already exists does nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - The id of the parent node.
+    /// * `child` - The id of the child node.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The child node already exists in the parent's ancestry (to prevent cycles).
+    /// - An attempt is made to add an edge from a node to itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the ids doesn't belong to this graph.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement the `Display` trait.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::ArenaGraph;
+    /// let mut graph = ArenaGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// graph.add_edge(parent, child).expect("Failed to add edge");
+    /// assert!(graph.add_edge(child, parent).is_err());
+    /// ```
+    pub fn add_edge(&mut self, parent: NodeId, child: NodeId) -> Result<(), AddEdgeError>
+    where
+        T: Display,
+    {
+        if parent == child {
+            return Err(SameNode(self.nodes[parent.0].value.to_string()));
+        }
+
+        if self.nodes[parent.0].childs.contains(&child) {
+            return Ok(());
+        }
+
+        if let Some(path) = self.find_in_parents(parent, child) {
+            // The path goes up from the parent to the child, the new edge would close it.
+            let cycle = path
+                .iter()
+                .rev()
+                .chain(path.last())
+                .map(|id| self.nodes[id.0].value.to_string())
+                .collect();
+
+            return Err(CyclicRelation(cycle));
+        }
+
+        self.nodes[parent.0].childs.push(child);
+        self.nodes[child.0].parents.push(parent);
+
+        Ok(())
+    }
+
+    /// Returns the value of a node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id doesn't belong to this graph.
+    pub fn value(&self, node: NodeId) -> &T {
+        &self.nodes[node.0].value
+    }
+
+    /// Returns the childs of a node, in the order their edges were added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id doesn't belong to this graph.
+    pub fn childs(&self, node: NodeId) -> &[NodeId] {
+        &self.nodes[node.0].childs
+    }
+
+    /// Returns the parents of a node, in the order their edges were added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id doesn't belong to this graph.
+    pub fn parents(&self, node: NodeId) -> &[NodeId] {
+        &self.nodes[node.0].parents
+    }
+
+    /// Iterates over the ids of all the nodes, in insertion order.
+    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.nodes.len()).map(NodeId)
+    }
+
+    /// Counts the nodes of the graph.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Counts the parent to child edges of the graph.
+    pub fn edge_count(&self) -> usize {
+        self.nodes.iter().map(|node| node.childs.len()).sum()
+    }
+
+    // Walks up the ancestry of `start` looking for `searched`, returning the ids leading to it
+    // when found. Each ancestor is explored once, with an explicit stack.
+    fn find_in_parents(&self, start: NodeId, searched: NodeId) -> Option<Vec<NodeId>> {
+        let mut visited = vec![false; self.nodes.len()];
+        visited[start.0] = true;
+
+        // Each frame holds a node of the current path and the position of its next parent.
+        let mut stack = vec![(start, 0)];
+
+        while let Some(&(node, parent_position)) = stack.last() {
+            let Some(&parent) = self.nodes[node.0].parents.get(parent_position) else {
+                stack.pop();
+                continue;
+            };
+
+            if let Some(last) = stack.last_mut() {
+                last.1 += 1;
+            }
+
+            if visited[parent.0] {
+                continue;
+            }
+            visited[parent.0] = true;
+
+            if parent == searched {
+                let mut path: Vec<_> = stack.into_iter().map(|(id, _)| id).collect();
+                path.push(parent);
+
+                return Some(path);
+            }
+
+            stack.push((parent, 0));
+        }
+
+        None
+    }
+}
+
+impl<T> Default for ArenaGraph<T> {
+    fn default() -> Self {
+        ArenaGraph::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arena_graph() {
+        let mut graph = ArenaGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+
+        assert_eq!(graph.get_or_add_node(1), node1);
+
+        assert!(graph.add_edge(node1, node2).is_ok());
+        assert!(graph.add_edge(node1, node3).is_ok());
+        assert!(graph.add_edge(node2, node4).is_ok());
+        assert!(graph.add_edge(node3, node4).is_ok());
+        assert!(graph.add_edge(node3, node4).is_ok());
+
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.edge_count(), 4);
+        assert_eq!(graph.childs(node1), &[node2, node3]);
+        assert_eq!(graph.parents(node4), &[node2, node3]);
+        assert_eq!(*graph.value(node4), 4);
+
+        assert!(matches!(graph.add_edge(node2, node2), Err(SameNode(_))));
+        assert!(matches!(
+            graph.add_edge(node4, node1),
+            Err(CyclicRelation(path)) if path == ["1", "2", "4", "1"]
+        ));
+    }
+
+    #[test]
+    fn test_arena_graph_is_send_and_sync() {
+        fn assert_send_sync<S: Send + Sync>() {}
+
+        assert_send_sync::<ArenaGraph<i32>>();
+        assert_send_sync::<NodeId>();
+    }
+}
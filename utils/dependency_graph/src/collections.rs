@@ -0,0 +1,38 @@
+// The collections used across the crate. With the `std` feature these are the hash based ones of
+// the standard library. `alloc` only offers ordered collections, so they stand in without it:
+// every key used in the crate is `Ord`, and the value index keeps bucketing nodes by hash.
+#[cfg(feature = "std")]
+pub(crate) use std::collections::{
+    hash_map::RandomState as HashState, HashMap as Map, HashSet as Set,
+};
+
+#[cfg(not(feature = "std"))]
+pub(crate) use alloc::collections::{BTreeMap as Map, BTreeSet as Set};
+
+// Without `std` there is no source of randomness, so the values are hashed with a fixed FNV-1a.
+#[cfg(not(feature = "std"))]
+pub(crate) type HashState = core::hash::BuildHasherDefault<FnvHasher>;
+
+#[cfg(not(feature = "std"))]
+pub(crate) struct FnvHasher(u64);
+
+#[cfg(not(feature = "std"))]
+impl Default for FnvHasher {
+    fn default() -> Self {
+        FnvHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl core::hash::Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use core::hash::{Hash, Hasher};
+
+/// Tells a `DependencyGraph` which values belong to the same node.
+///
+/// Like `Hash` and `Eq`, the two methods must agree: values for which `same_value` returns
+/// `true` must feed the same data to the hasher in `hash_value`.
+pub trait ValueKey<T> {
+    /// Feeds what identifies `value` to the hasher.
+    fn hash_value<H: Hasher>(&self, value: &T, state: &mut H);
+
+    /// Tells whether two values belong to the same node.
+    fn same_value(&self, left: &T, right: &T) -> bool;
+}
+
+/// Deduplicates values as a whole, through their `Hash` and `Eq` implementations.
+///
+/// This is the key of the graphs created by `DependencyGraph::new`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ByValue;
+
+impl<T: Hash + Eq> ValueKey<T> for ByValue {
+    fn hash_value<H: Hasher>(&self, value: &T, state: &mut H) {
+        value.hash(state);
+    }
+
+    fn same_value(&self, left: &T, right: &T) -> bool {
+        left == right
+    }
+}
+
+/// Deduplicates values by the key a function extracts from them.
+///
+/// Created by `DependencyGraph::with_key`. Only the extracted key has to implement `Hash` and
+/// `Eq`, the values themselves don't.
+#[derive(Clone, Copy)]
+pub struct ByKey<F>(pub(crate) F);
+
+impl<T, K, F> ValueKey<T> for ByKey<F>
+where
+    F: Fn(&T) -> K,
+    K: Hash + Eq,
+{
+    fn hash_value<H: Hasher>(&self, value: &T, state: &mut H) {
+        (self.0)(value).hash(state);
+    }
+
+    fn same_value(&self, left: &T, right: &T) -> bool {
+        (self.0)(left) == (self.0)(right)
+    }
+}
@@ -1,13 +1,42 @@
 #![allow(dead_code)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::collections::{BinaryHeap, VecDeque};
+use alloc::format;
+use alloc::rc::{Rc, Weak};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use collections::{HashState, Map, Set};
+use core::cell::RefCell;
+use core::cmp::Reverse;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::hash::{BuildHasher, Hash, Hasher};
+use core::mem;
+use core::slice;
 use node::Node;
-use std::cell::RefCell;
-use std::fmt::{Debug, Display};
-use std::rc::{Rc, Weak};
-use thiserror::Error;
-use AddEdgeError::{CyclicRelation, SameNode};
+use AddEdgeError::{CyclicRelation, ForeignNode, InBatch, SameNode, ValueTaken};
 
+mod arena;
+mod collections;
+mod key;
 mod node;
+mod reachability;
+#[cfg(feature = "serde")]
+mod serialization;
+#[cfg(feature = "std")]
+mod sync;
+mod traversal;
+
+pub use arena::{ArenaGraph, NodeId};
+pub use key::{ByKey, ByValue, ValueKey};
+pub use reachability::Reachability;
+#[cfg(feature = "std")]
+pub use sync::{SyncDependencyGraph, SyncNode};
+pub use traversal::{Bfs, Dfs, DfsOrder, IntoValues};
 
 type RefNode<T> = Rc<RefCell<Node<T>>>;
 type WeakRefNode<T> = Weak<RefCell<Node<T>>>;
@@ -16,19 +45,88 @@ type WeakRefNode<T> = Weak<RefCell<Node<T>>>;
 ///
 /// `DependencyGraph<T>` represents a directed graph where nodes contain values of type `T`.
 /// It allows for adding nodes and edges, as well as querying the graph structure.
-pub struct DependencyGraph<T> {
+///
+/// The key `K` tells which values share a node. It defaults to `ByValue`, which compares
+/// whole values, while `with_key` creates a graph comparing a key extracted from them.
+pub struct DependencyGraph<T, K = ByValue> {
     nodes: Vec<RefNode<T>>,
+    // Nodes bucketed by the hash of their value, so existing values are found without a full scan.
+    index: Map<u64, Vec<RefNode<T>>>,
+    hash_builder: HashState,
+    // Decides which values share a node, `ByValue` unless built with `with_key`.
+    key: K,
 }
 
-#[derive(Error, Debug)]
+#[derive(Debug)]
 pub enum AddEdgeError {
-    #[error("Failed to add node as it is already referenced in its ancestry: {0}")]
-    CyclicRelation(String),
-
-    #[error("Can't add edge to itself: {0}")]
+    CyclicRelation(Vec<String>),
     SameNode(String),
+    ValueTaken(String),
+    ForeignNode(String),
+    InBatch {
+        index: usize,
+        parent: String,
+        child: String,
+        source: Box<AddEdgeError>,
+    },
+}
+
+impl Display for AddEdgeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CyclicRelation(path) => write!(
+                f,
+                "Failed to add node as it is already referenced in its ancestry: {}",
+                path.join(" -> ")
+            ),
+            SameNode(value) => write!(f, "Can't add edge to itself: {value}"),
+            ValueTaken(value) => write!(f, "A node already holds the value: {value}"),
+            ForeignNode(value) => write!(f, "The node doesn't belong to this graph: {value}"),
+            InBatch {
+                index,
+                parent,
+                child,
+                source,
+            } => write!(
+                f,
+                "Failed to add edge {index} of the batch ({parent} -> {child}): {source}"
+            ),
+        }
+    }
+}
+
+// `std::error::Error` has no `alloc` counterpart, so without `std` the errors only implement
+// `Display`.
+#[cfg(feature = "std")]
+impl std::error::Error for AddEdgeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InBatch { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CycleError {
+    Cycle(Vec<String>),
+}
+
+impl Display for CycleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CycleError::Cycle(path) => {
+                write!(f, "The graph contains a cycle: {}", path.join(" -> "))
+            }
+        }
+    }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for CycleError {}
+
+// The constructors and the functions working on nodes alone are only defined for the default
+// key, so that calls like `DependencyGraph::add_edge` don't have to name one.
 impl<T> DependencyGraph<T> {
     /// Creates a new, empty `DependencyGraph<T>`.
     ///
@@ -43,60 +141,86 @@ impl<T> DependencyGraph<T> {
     /// let graph: DependencyGraph<i32> = DependencyGraph::new();
     /// ```
     pub fn new() -> DependencyGraph<T> {
-        DependencyGraph { nodes: Vec::new() }
+        DependencyGraph::with_capacity(0)
     }
 
-    /// Retrieves an existing node with the given value or adds a new node if it doesn't exist.
+    /// Creates a new, empty `DependencyGraph<T>` with room for at least `capacity` nodes.
+    ///
+    /// Both the node list and the value index are allocated upfront, so no reallocation
+    /// happens until `capacity` nodes have been added. Without the `std` feature the index is
+    /// an ordered map, which can't be preallocated, and only the node list is.
     ///
     /// # Arguments
     ///
-    /// * `value` - The value to search for or add to the graph.
+    /// * `capacity` - The number of nodes to allocate room for.
     ///
     /// # Returns
     ///
-    /// A reference-counted, mutable reference to the node (`RefNode<T>`).
+    /// A new `DependencyGraph<T>` instance with no nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let graph: DependencyGraph<i32> = DependencyGraph::with_capacity(1000);
+    ///
+    /// assert_eq!(graph.node_count(), 0);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> DependencyGraph<T> {
+        DependencyGraph::with_capacity_and_key(capacity, ByValue)
+    }
+
+    /// Creates a new, empty `DependencyGraph<T>` deduplicating values by a key.
+    ///
+    /// `get_or_add_node` and the other lookups then hash and compare the keys extracted by
+    /// `key` rather than whole values, so `T` needs neither `Hash` nor `Eq`. This suits rich
+    /// metadata identified by a path, for instance. Two values with the same key share a
+    /// node, the first one added being kept. Graphs derived from this one, such as `reversed`,
+    /// use the same key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The function extracting the key of a value.
+    ///
+    /// # Returns
+    ///
+    /// A new `DependencyGraph<T, ByKey<F>>` instance with no nodes.
     ///
     /// # Type Constraints
     ///
-    /// The type `T` must implement the `Eq` trait.
+    /// The key type `Q` must implement both the `Hash` and `Eq` traits.
     ///
     /// # Examples
     ///
     /// ```
     /// use dependency_graph::DependencyGraph;
-    /// let mut graph = DependencyGraph::new(); 
-    /// 
-    /// let node = graph.get_or_add_node(42);
+    ///
+    /// struct Meta {
+    ///     path: String,
+    ///     size: f64,
+    /// }
+    ///
+    /// let mut graph = DependencyGraph::with_key(|meta: &Meta| meta.path.clone());
+    ///
+    /// let node = graph.get_or_add_node(Meta { path: String::from("a"), size: 1.0 });
+    /// let same = graph.get_or_add_node(Meta { path: String::from("a"), size: 2.0 });
+    ///
+    /// assert_eq!(graph.node_count(), 1);
+    /// assert_eq!(same.borrow().get_value().size, 1.0);
     /// ```
-    pub fn get_or_add_node(&mut self, value: T) -> RefNode<T>
+    pub fn with_key<Q, F>(key: F) -> DependencyGraph<T, ByKey<F>>
     where
-        T: Eq,
+        F: Fn(&T) -> Q,
+        Q: Hash + Eq,
     {
-        if let Some(node) = self.fetch_existing(&value) {
-            return node;
-        }
-
-        let node = Node::new(value);
-
-        let ref_node = Rc::new(RefCell::new(node));
-
-        self.nodes.push(Rc::clone(&ref_node));
-
-        ref_node
-    }
-
-    fn fetch_existing(&self, value: &T) -> Option<RefNode<T>>
-    where
-        T: Eq,
-    {
-        self.nodes
-            .iter()
-            .find(|node_ref| node_ref.borrow().value == *value)
-            .map(Rc::clone)
+        DependencyGraph::with_capacity_and_key(0, ByKey(key))
     }
 
     /// Adds an edge between two nodes in the graph.
     ///
+    /// Adding an edge that already exists does nothing, so a child is never listed twice.
+    /// The edge weighs `1.0`, use `add_weighted_edge` to choose another weight.
+    ///
     /// # Arguments
     ///
     /// * `parent_ref` - A reference to the parent node.
@@ -114,7 +238,7 @@ impl<T> DependencyGraph<T> {
     ///
     /// # Type Constraints
     ///
-    /// The type `T` must implement both the `Eq` and `Display` traits.
+    /// The type `T` must implement the `Display` trait.
     ///
     /// # Examples
     ///
@@ -129,104 +253,4715 @@ impl<T> DependencyGraph<T> {
     /// ```
     pub fn add_edge(parent_ref: &RefNode<T>, child_ref: &RefNode<T>) -> Result<(), AddEdgeError>
     where
-        T: Eq + Display,
+        T: Display,
+    {
+        Self::add_weighted_edge(parent_ref, child_ref, 1.0)
+    }
+
+    /// Adds an edge carrying a weight between two nodes in the graph.
+    ///
+    /// This behaves like `add_edge`, which is the same as a weight of `1.0`.
+    /// Adding an edge that already exists does nothing, its weight is left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent_ref` - A reference to the parent node.
+    /// * `child_ref` - A reference to the child node.
+    /// * `weight` - The weight of the edge, such as the cost of the dependency.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<(), AddEdgeError>` indicating success or containing an error if the operation failed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The child node already exists in the parent's ancestry (to prevent cycles).
+    /// - An attempt is made to add an edge from a node to itself.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement the `Display` trait.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_weighted_edge(&parent, &child, 2.5).expect("Failed to add edge");
+    ///
+    /// assert_eq!(DependencyGraph::edge_weight(&parent, &child), Some(2.5));
+    /// ```
+    pub fn add_weighted_edge(
+        parent_ref: &RefNode<T>,
+        child_ref: &RefNode<T>,
+        weight: f64,
+    ) -> Result<(), AddEdgeError>
+    where
+        T: Display,
     {
         if Rc::ptr_eq(parent_ref, child_ref) {
-            return Err(SameNode(
-                parent_ref.borrow().value.to_string(),
-            ));
+            return Err(SameNode(parent_ref.borrow().value.to_string()));
         }
 
-        verify_if_exists_in_parents(parent_ref, child_ref)?;
+        let already_linked = parent_ref.borrow().has_child(child_ref);
+        if already_linked {
+            return Ok(());
+        }
+
+        // An edge following the levels can't close a cycle, nor can an edge from a node without
+        // parents or to a node without childs. Otherwise the pruned search tells whether there
+        // is one, and the ancestry walk only runs to report it.
+        let acyclic = {
+            let parent = parent_ref.borrow();
+            let child = child_ref.borrow();
+            parent.level < child.level
+                || child.childs.is_empty()
+                || parent.live_parents().next().is_none()
+        };
+        if !acyclic && reaches_within_levels(child_ref, parent_ref) {
+            verify_if_exists_in_parents(parent_ref, child_ref)?;
+        }
 
-        parent_ref.borrow_mut().add_child(child_ref);
-        child_ref.borrow_mut().add_parent(parent_ref);
+        link(parent_ref, child_ref, weight);
 
         Ok(())
     }
-}
 
-impl<T> Default for DependencyGraph<T> {
-    fn default() -> Self {
-        DependencyGraph::new()
+    /// Adds an edge between two nodes without checking for cycles.
+    ///
+    /// This skips the ancestry walk of `add_edge`, which dominates the cost of importing a
+    /// graph already known to be acyclic. The edge weighs `1.0`, and an edge that already
+    /// exists is not added twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent_ref` - A reference to the parent node.
+    /// * `child_ref` - A reference to the child node.
+    ///
+    /// # Safety
+    ///
+    /// **The caller must guarantee that the edge doesn't link a node to itself and doesn't
+    /// close a cycle.**
+    ///
+    /// `unsafe` marks a logic contract here, not a soundness requirement: breaking it can't
+    /// cause undefined behavior. The graph is only left in a state its other methods don't
+    /// expect, the ordering methods then fail with a `CycleError` and the nodes of the cycle
+    /// leak, as their `Rc` links keep each other alive. The justification at the call site is
+    /// about acyclicity, there is no memory invariant to reason about. `validate_acyclic` can
+    /// check the result afterwards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// // SAFETY: the graph holds no other edge, so this one can't close a cycle.
+    /// unsafe { DependencyGraph::add_edge_unchecked(&parent, &child) };
+    ///
+    /// assert!(parent.borrow().has_child(&child));
+    /// ```
+    pub unsafe fn add_edge_unchecked(parent_ref: &RefNode<T>, child_ref: &RefNode<T>) {
+        let already_linked = parent_ref.borrow().has_child(child_ref);
+        if already_linked {
+            return;
+        }
+
+        link(parent_ref, child_ref, 1.0);
     }
-}
 
-fn verify_if_exists_in_parents<T: Eq + Display>(
-    parent_ref: &RefNode<T>,
-    child_ref: &RefNode<T>,
-) -> Result<(), AddEdgeError> {
-    let parent_node = parent_ref.borrow();
+    /// Reads the weight of the edge between two nodes.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent_ref` - A reference to the parent node.
+    /// * `child_ref` - A reference to the child node.
+    ///
+    /// # Returns
+    ///
+    /// `Some` weight if the edge exists, `None` otherwise. Edges added with `add_edge`
+    /// weigh `1.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert_eq!(DependencyGraph::edge_weight(&parent, &child), Some(1.0));
+    /// assert_eq!(DependencyGraph::edge_weight(&child, &parent), None);
+    /// ```
+    pub fn edge_weight(parent_ref: &RefNode<T>, child_ref: &RefNode<T>) -> Option<f64> {
+        parent_ref.borrow().get_child_weight(child_ref)
+    }
 
-    if Rc::ptr_eq(parent_ref, child_ref) {
-        return Err(CyclicRelation(parent_node.value.to_string()));
+    /// Checks whether `add_edge` would accept an edge between two nodes, without adding it.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent_ref` - A reference to the parent node.
+    /// * `child_ref` - A reference to the child node.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the edge would link a node to itself or introduce a cycle, `true` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert!(!DependencyGraph::can_add_edge(&child, &parent));
+    /// assert!(!DependencyGraph::can_add_edge(&child, &child));
+    /// ```
+    pub fn can_add_edge(parent_ref: &RefNode<T>, child_ref: &RefNode<T>) -> bool {
+        !Rc::ptr_eq(parent_ref, child_ref) && find_in_parents(parent_ref, child_ref).is_none()
     }
 
-    parent_node.parents.iter().try_for_each(|parent_weak_ref| {
-        if let Some(parent_ref) = parent_weak_ref.upgrade() {
-            verify_if_exists_in_parents(&parent_ref, child_ref)
-        } else {
-            Ok(())
+    /// Removes the edge between two nodes.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent_ref` - A reference to the parent node.
+    /// * `child_ref` - A reference to the child node.
+    ///
+    /// # Returns
+    ///
+    /// `true` if an edge was removed, `false` if there was no such edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert!(DependencyGraph::remove_edge(&parent, &child));
+    /// assert!(!DependencyGraph::remove_edge(&parent, &child));
+    /// ```
+    pub fn remove_edge(parent_ref: &RefNode<T>, child_ref: &RefNode<T>) -> bool {
+        if Rc::ptr_eq(parent_ref, child_ref) {
+            return false;
         }
-    })?;
-
-    Ok(())
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_dep_graph() {
-        let mut graph = DependencyGraph::new();
-        let node1 = graph.get_or_add_node(1);
-        let node2 = graph.get_or_add_node(2);
-        let node3 = graph.get_or_add_node(3);
-        let node4 = graph.get_or_add_node(4);
-
-        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
-        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
-        assert!(DependencyGraph::add_edge(&node2, &node4).is_ok());
-        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
-
-        let node1 = node1.borrow();
-        let node2 = node2.borrow();
-        let node3 = node3.borrow();
-        let node4 = node4.borrow();
-
-        assert_eq!(node1.childs.len(), 2);
-        assert_eq!(node1.parents.len(), 0);
 
-        assert_eq!(node2.childs.len(), 1);
-        assert_eq!(node2.parents.len(), 1);
+        let removed_child = parent_ref.borrow_mut().remove_child(child_ref);
+        let removed_parent = child_ref.borrow_mut().remove_parent(parent_ref);
 
-        assert_eq!(node3.childs.len(), 1);
-        assert_eq!(node3.parents.len(), 1);
+        removed_child || removed_parent
+    }
 
-        assert_eq!(node4.childs.len(), 0);
-        assert_eq!(node4.parents.len(), 2);
+    /// Counts the edges going out of a node, towards its childs.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node to inspect.
+    ///
+    /// # Returns
+    ///
+    /// The number of childs of `node`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert_eq!(DependencyGraph::out_degree(&parent), 1);
+    /// ```
+    pub fn out_degree(node: &RefNode<T>) -> usize {
+        node.borrow().childs.len()
     }
 
-    #[test]
-    fn test_cyclic_graph_error() {
-        let mut graph = DependencyGraph::new();
-        let node1 = graph.get_or_add_node(1);
-        let node2 = graph.get_or_add_node(2);
+    /// Counts the edges coming into a node, from its parents.
+    ///
+    /// Only live parents are counted: a parent that has been dropped doesn't count.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node to inspect.
+    ///
+    /// # Returns
+    ///
+    /// The number of live parents of `node`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert_eq!(DependencyGraph::in_degree(&child), 1);
+    /// ```
+    pub fn in_degree(node: &RefNode<T>) -> usize {
+        node.borrow().live_parents().count()
+    }
+
+    /// Iterates breadth-first over the nodes reachable from a starting node.
+    ///
+    /// The nodes are produced lazily: the starting node first, then its childs, then their
+    /// childs, and so on. Each node is yielded once, even in diamond-shaped graphs.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The node to start from.
+    ///
+    /// # Returns
+    ///
+    /// A `Bfs<T>` iterator yielding the reachable nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let root = graph.get_or_add_node(1);
+    /// let left = graph.get_or_add_node(2);
+    /// let right = graph.get_or_add_node(3);
+    /// let bottom = graph.get_or_add_node(4);
+    ///
+    /// DependencyGraph::add_edge(&root, &left).expect("Failed to add edge");
+    /// DependencyGraph::add_edge(&root, &right).expect("Failed to add edge");
+    /// DependencyGraph::add_edge(&left, &bottom).expect("Failed to add edge");
+    /// DependencyGraph::add_edge(&right, &bottom).expect("Failed to add edge");
+    ///
+    /// let values: Vec<i32> = DependencyGraph::bfs(&root)
+    ///     .map(|node| *node.borrow().get_value())
+    ///     .collect();
+    ///
+    /// assert_eq!(values, vec![1, 2, 3, 4]);
+    /// ```
+    pub fn bfs(start: &RefNode<T>) -> Bfs<T> {
+        Bfs::new(start)
+    }
+
+    /// Iterates depth-first over the nodes reachable from a starting node.
+    ///
+    /// With `DfsOrder::PreOrder` a node is yielded before its childs, with `DfsOrder::PostOrder`
+    /// after all of them, which gives a valid teardown sequence. Childs are explored in the order
+    /// their edges were added. Each node is yielded once, and a cycle can't cause an infinite loop.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The node to start from.
+    /// * `order` - Whether nodes are yielded before or after their childs.
+    ///
+    /// # Returns
+    ///
+    /// A `Dfs<T>` iterator yielding the reachable nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::{DependencyGraph, DfsOrder};
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// let values: Vec<i32> = DependencyGraph::dfs(&parent, DfsOrder::PostOrder)
+    ///     .map(|node| *node.borrow().get_value())
+    ///     .collect();
+    ///
+    /// assert_eq!(values, vec![2, 1]);
+    /// ```
+    pub fn dfs(start: &RefNode<T>, order: DfsOrder) -> Dfs<T> {
+        Dfs::new(start, order)
+    }
+
+    /// Collects every node transitively reachable through the `childs` links.
+    ///
+    /// The nodes are returned breadth-first, so closer relations come first,
+    /// and each appears once even when reachable through several paths.
+    /// The starting node is not included.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node to start from.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of the descendants of `node`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert_eq!(DependencyGraph::descendants(&parent).len(), 1);
+    /// ```
+    pub fn descendants(node: &RefNode<T>) -> Vec<RefNode<T>> {
+        breadth_first(node, |node_ref| node_ref.childs.to_vec())
+    }
+
+    /// Collects every node transitively reachable through the `parents` links.
+    ///
+    /// The nodes are returned breadth-first, so closer relations come first,
+    /// and each appears once even when reachable through several paths.
+    /// Parents that have been dropped are ignored. The starting node is not included.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node to start from.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of the ancestors of `node`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert_eq!(DependencyGraph::ancestors(&child).len(), 1);
+    /// ```
+    pub fn ancestors(node: &RefNode<T>) -> Vec<RefNode<T>> {
+        breadth_first(node, |node_ref| node_ref.live_parents().collect())
+    }
+
+    /// Walks up from a node to one of its roots, following the first live parent each time.
+    ///
+    /// This explains why a node is part of the graph, but when it is reachable through several
+    /// parents only one of the chains is returned, so the path is not guaranteed to be unique.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node to start from.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of the walked nodes, starting with `node` and ending with a node that has no
+    /// live parent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// graph.add_edges([(1, 2), (2, 3)]).expect("Failed to add edges");
+    ///
+    /// let leaf = graph.get_or_add_node(3);
+    /// let path = DependencyGraph::path_to_root(&leaf);
+    /// assert_eq!(*path[2].borrow().get_value(), 1);
+    /// ```
+    pub fn path_to_root(node: &RefNode<T>) -> Vec<RefNode<T>> {
+        let mut path = vec![Rc::clone(node)];
+
+        loop {
+            let parent = path
+                .last()
+                .and_then(|last| last.borrow().live_parents().next());
+
+            // Graphs wired with `add_edge` are acyclic, the check only guards forced cycles.
+            match parent {
+                Some(parent) if !path.iter().any(|walked| Rc::ptr_eq(walked, &parent)) => {
+                    path.push(parent);
+                }
+                _ => return path,
+            }
+        }
+    }
+
+    /// Checks whether a node can be reached from another by following the `childs` links.
+    ///
+    /// The search is depth-first and stops at the first match, each node being explored once.
+    /// A node is always reachable from itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The node to start from.
+    /// * `to` - The node to look for.
+    ///
+    /// # Returns
+    ///
+    /// `true` if there is a path from `from` to `to`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let node1 = graph.get_or_add_node(1);
+    /// let node2 = graph.get_or_add_node(2);
+    /// let node3 = graph.get_or_add_node(3);
+    ///
+    /// DependencyGraph::add_edge(&node1, &node2).expect("Failed to add edge");
+    /// DependencyGraph::add_edge(&node2, &node3).expect("Failed to add edge");
+    ///
+    /// assert!(DependencyGraph::has_path(&node1, &node3));
+    /// assert!(!DependencyGraph::has_path(&node3, &node1));
+    /// ```
+    pub fn has_path(from: &RefNode<T>, to: &RefNode<T>) -> bool {
+        let mut visited = Set::from([Rc::as_ptr(from)]);
+        let mut stack = vec![Rc::clone(from)];
+
+        while let Some(node_ref) = stack.pop() {
+            if Rc::ptr_eq(&node_ref, to) {
+                return true;
+            }
+
+            for child_ref in &node_ref.borrow().childs {
+                if visited.insert(Rc::as_ptr(child_ref)) {
+                    stack.push(Rc::clone(child_ref));
+                }
+            }
+        }
+
+        false
+    }
+}
+
+impl<T, K> DependencyGraph<T, K> {
+    // The constructors of `DependencyGraph<T>` delegate here, as do the methods building a new
+    // graph with the key of this one.
+    fn with_capacity_and_key(capacity: usize, key: K) -> DependencyGraph<T, K> {
+        DependencyGraph {
+            nodes: Vec::with_capacity(capacity),
+            #[cfg(feature = "std")]
+            index: Map::with_capacity(capacity),
+            #[cfg(not(feature = "std"))]
+            index: Map::new(),
+            hash_builder: HashState::default(),
+            key,
+        }
+    }
+
+    /// Retrieves an existing node with the given value or adds a new node if it doesn't exist.
+    ///
+    /// The lookup goes through a hash index of the values, so it runs in amortized constant time.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to search for or add to the graph.
+    ///
+    /// # Returns
+    ///
+    /// A reference-counted, mutable reference to the node (`RefNode<T>`).
+    ///
+    /// # Type Constraints
+    ///
+    /// The key `K` must implement `ValueKey<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new(); 
+    /// 
+    /// let node = graph.get_or_add_node(42);
+    /// ```
+    pub fn get_or_add_node(&mut self, value: T) -> RefNode<T>
+    where
+        K: ValueKey<T>,
+    {
+        self.get_or_add_node_entry(value).0
+    }
+
+    /// Retrieves or adds the node holding the given value, telling which of the two happened.
+    ///
+    /// This works like `get_or_add_node` and saves a `contains` call beforehand when the
+    /// caller needs to know whether the value was already present.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to search for or add to the graph.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the node and `true` if it was just added, `false` if it already existed.
+    ///
+    /// # Type Constraints
+    ///
+    /// The key `K` must implement `ValueKey<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let (_, added) = graph.get_or_add_node_entry(42);
+    /// assert!(added);
+    ///
+    /// let (_, added) = graph.get_or_add_node_entry(42);
+    /// assert!(!added);
+    /// ```
+    pub fn get_or_add_node_entry(&mut self, value: T) -> (RefNode<T>, bool)
+    where
+        K: ValueKey<T>,
+    {
+        if let Some(node) = self.fetch_existing(&value) {
+            return (node, false);
+        }
+
+        let hash = self.hash_value(&value);
+
+        let node = Node::new(value);
+
+        let ref_node = Rc::new(RefCell::new(node));
+
+        self.nodes.push(Rc::clone(&ref_node));
+        self.index
+            .entry(hash)
+            .or_default()
+            .push(Rc::clone(&ref_node));
+
+        (ref_node, true)
+    }
+
+    /// Retrieves the node holding the given value, without adding anything to the graph.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to search for.
+    ///
+    /// # Returns
+    ///
+    /// `Some` node if the value is part of the graph, `None` otherwise.
+    ///
+    /// # Type Constraints
+    ///
+    /// The key `K` must implement `ValueKey<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// graph.get_or_add_node(42);
+    ///
+    /// assert!(graph.get_node(&42).is_some());
+    /// assert!(graph.get_node(&7).is_none());
+    /// ```
+    pub fn get_node(&self, value: &T) -> Option<RefNode<T>>
+    where
+        K: ValueKey<T>,
+    {
+        self.fetch_existing(value)
+    }
+
+    /// Checks whether a value is part of the graph.
+    ///
+    /// Thanks to the hash index of the values, this runs in constant time.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to search for.
+    ///
+    /// # Returns
+    ///
+    /// `true` if a node holds the value.
+    ///
+    /// # Type Constraints
+    ///
+    /// The key `K` must implement `ValueKey<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// graph.get_or_add_node(42);
+    ///
+    /// assert!(graph.contains(&42));
+    /// assert!(!graph.contains(&7));
+    /// ```
+    pub fn contains(&self, value: &T) -> bool
+    where
+        K: ValueKey<T>,
+    {
+        self.fetch_existing(value).is_some()
+    }
+
+    fn fetch_existing(&self, value: &T) -> Option<RefNode<T>>
+    where
+        K: ValueKey<T>,
+    {
+        self.index
+            .get(&self.hash_value(value))?
+            .iter()
+            .find(|node_ref| self.same_value(&node_ref.borrow().value, value))
+            .map(Rc::clone)
+    }
+
+    fn hash_value(&self, value: &T) -> u64
+    where
+        K: ValueKey<T>,
+    {
+        let mut state = self.hash_builder.build_hasher();
+        self.key.hash_value(value, &mut state);
+        state.finish()
+    }
+
+    fn same_value(&self, left: &T, right: &T) -> bool
+    where
+        K: ValueKey<T>,
+    {
+        self.key.same_value(left, right)
+    }
+
+    // Returns whether the node is indexed, that is whether it belongs to the graph.
+    fn is_indexed(&self, node: &RefNode<T>) -> bool
+    where
+        K: ValueKey<T>,
+    {
+        self.index
+            .get(&self.hash_value(&node.borrow().value))
+            .is_some_and(|bucket| bucket.iter().any(|node_ref| Rc::ptr_eq(node_ref, node)))
+    }
+
+    // Drops the node from the hash index, leaving `self.nodes` untouched.
+    fn unindex(&mut self, node: &RefNode<T>)
+    where
+        K: ValueKey<T>,
+    {
+        let hash = self.hash_value(&node.borrow().value);
+
+        let Some(bucket) = self.index.get_mut(&hash) else {
+            return;
+        };
+
+        bucket.retain(|node_ref| !Rc::ptr_eq(node_ref, node));
+
+        if bucket.is_empty() {
+            self.index.remove(&hash);
+        }
+    }
+
+    /// Replaces the value held by a node.
+    ///
+    /// The hash index is updated so the node is found under its new value. Renaming doesn't
+    /// alter the edges, the tags or the position of the node.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node to rename.
+    /// * `new_value` - The value the node should hold.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<(), AddEdgeError>` indicating success or failure.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The node doesn't belong to this graph, as the index of its own graph would then be
+    ///   left behind. `ForeignNode` is returned with the current value of the node.
+    /// - Another node of the graph already holds `new_value`, `ValueTaken` is returned.
+    ///
+    /// In both cases the node keeps its value.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement the `Display` trait, and the key `K` must implement
+    /// `ValueKey<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let node = graph.get_or_add_node(1);
+    ///
+    /// graph.rename_node(&node, 2).expect("No other node holds 2");
+    ///
+    /// assert!(graph.contains(&2));
+    /// assert!(!graph.contains(&1));
+    /// ```
+    pub fn rename_node(&mut self, node: &RefNode<T>, new_value: T) -> Result<(), AddEdgeError>
+    where
+        T: Display,
+        K: ValueKey<T>,
+    {
+        if !self.is_indexed(node) {
+            return Err(ForeignNode(node.borrow().value.to_string()));
+        }
+
+        if let Some(existing) = self.fetch_existing(&new_value) {
+            if !Rc::ptr_eq(&existing, node) {
+                return Err(ValueTaken(new_value.to_string()));
+            }
+        }
+
+        self.unindex(node);
+        let hash = self.hash_value(&new_value);
+        node.borrow_mut().value = new_value;
+        self.index.entry(hash).or_default().push(Rc::clone(node));
+
+        Ok(())
+    }
+
+    /// Adds a batch of edges given as `(parent, child)` value pairs.
+    ///
+    /// Each endpoint is looked up or added through `get_or_add_node`, then the two nodes are
+    /// linked with `add_edge`. The pairs are processed in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `edges` - The `(parent, child)` pairs to link.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<(), AddEdgeError>` indicating success or containing the first error met.
+    ///
+    /// # Errors
+    ///
+    /// The batch stops at the first pair `add_edge` rejects. Its error is returned wrapped in
+    /// `InBatch`, along with the zero-based index of the pair in `edges` and the parent and
+    /// child values rendered with `Display`. The nodes and edges added before that pair are kept.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement the `Display` trait, and the key `K` must implement
+    /// `ValueKey<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// graph.add_edges([(1, 2), (2, 3), (1, 3)]).expect("Failed to add edges");
+    ///
+    /// assert_eq!(graph.node_count(), 3);
+    /// assert_eq!(graph.edge_count(), 3);
+    /// ```
+    pub fn add_edges<I>(&mut self, edges: I) -> Result<(), AddEdgeError>
+    where
+        I: IntoIterator<Item = (T, T)>,
+        T: Display,
+        K: ValueKey<T>,
+    {
+        for (index, (parent, child)) in edges.into_iter().enumerate() {
+            let parent_ref = self.get_or_add_node(parent);
+            let child_ref = self.get_or_add_node(child);
+
+            DependencyGraph::add_edge(&parent_ref, &child_ref).map_err(|source| InBatch {
+                index,
+                parent: parent_ref.borrow().value.to_string(),
+                child: child_ref.borrow().value.to_string(),
+                source: Box::new(source),
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes a node from the graph along with all the edges it is part of.
+    ///
+    /// The node is dropped from the graph, from the `parents` of each of its childs and from
+    /// the `childs` of each of its parents. Weak references to already dropped parents met
+    /// along the way are pruned. The removed node keeps no edges, so holding on to it doesn't
+    /// keep its former childs alive.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node to remove.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the node was part of the graph, `false` otherwise.
+    ///
+    /// # Type Constraints
+    ///
+    /// The key `K` must implement `ValueKey<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert!(graph.remove_node(&child));
+    /// assert!(parent.borrow().get_childs().is_empty());
+    /// assert!(!graph.remove_node(&child));
+    /// ```
+    pub fn remove_node(&mut self, node: &RefNode<T>) -> bool
+    where
+        K: ValueKey<T>,
+    {
+        let Some(position) = self
+            .nodes
+            .iter()
+            .position(|node_ref| Rc::ptr_eq(node_ref, node))
+        else {
+            return false;
+        };
+
+        self.nodes.remove(position);
+        self.unindex(node);
+
+        let (childs, parents) = {
+            let mut removed = node.borrow_mut();
+            removed.child_weights.clear();
+            (
+                mem::take(&mut removed.childs),
+                mem::take(&mut removed.parents),
+            )
+        };
+
+        for child_ref in &childs {
+            child_ref.borrow_mut().remove_parent(node);
+        }
+
+        for parent_ref in parents.iter().filter_map(|parent| parent.upgrade()) {
+            parent_ref.borrow_mut().remove_child(node);
+        }
+
+        true
+    }
+
+    /// Iterates over all the nodes of the graph, in insertion order.
+    ///
+    /// # Returns
+    ///
+    /// An iterator yielding a new reference to each node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// graph.get_or_add_node(1);
+    /// graph.get_or_add_node(2);
+    ///
+    /// assert_eq!(graph.nodes().count(), 2);
+    /// ```
+    pub fn nodes(&self) -> impl Iterator<Item = RefNode<T>> + '_ {
+        self.nodes.iter().map(Rc::clone)
+    }
+
+    /// Collects the values of all the nodes, in insertion order.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of clones of the node values.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement the `Clone` trait.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// graph.get_or_add_node(2);
+    /// graph.get_or_add_node(1);
+    ///
+    /// assert_eq!(graph.values(), vec![2, 1]);
+    /// ```
+    pub fn values(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.nodes
+            .iter()
+            .map(|node_ref| node_ref.borrow().value.clone())
+            .collect()
+    }
+
+    /// Counts the nodes of the graph.
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// graph.get_or_add_node(1);
+    /// graph.get_or_add_node(2);
+    ///
+    /// assert_eq!(graph.node_count(), 2);
+    /// ```
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Checks whether the graph has no node.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the graph holds no node, `false` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    /// assert!(graph.is_empty());
+    ///
+    /// graph.get_or_add_node(1);
+    /// assert!(!graph.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Removes every node and edge, keeping the allocated memory for reuse.
+    ///
+    /// Parents are only weakly referenced, so the nodes are freed as soon as the graph lets go
+    /// of them, unless a `RefNode<T>` is still held elsewhere.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// graph.add_edges([(1, 2), (2, 3)]).expect("Failed to add edges");
+    /// graph.clear();
+    ///
+    /// assert!(graph.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.index.clear();
+    }
+
+    /// Takes every node out of the graph, leaving it empty.
+    ///
+    /// The nodes keep their edges, so the returned nodes still reference each other and can be
+    /// walked or moved into another structure.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of the nodes, in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// graph.add_edges([(1, 2)]).expect("Failed to add edges");
+    ///
+    /// let nodes = graph.drain();
+    /// assert!(graph.is_empty());
+    /// assert_eq!(nodes[0].borrow().get_childs().len(), 1);
+    /// ```
+    pub fn drain(&mut self) -> Vec<RefNode<T>> {
+        self.index.clear();
+        mem::take(&mut self.nodes)
+    }
+
+    /// Counts the edges of the graph.
+    ///
+    /// Edges are directed from parent to child, each one is counted once
+    /// from the `childs` of its parent.
+    ///
+    /// # Returns
+    ///
+    /// The number of parent to child edges in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert_eq!(graph.edge_count(), 1);
+    /// ```
+    pub fn edge_count(&self) -> usize {
+        self.nodes
+            .iter()
+            .map(|node_ref| node_ref.borrow().childs.len())
+            .sum()
+    }
+
+    /// Retrieves the nodes without any parent, in insertion order.
+    ///
+    /// Parents that have been dropped don't count, so a node whose parents were all removed
+    /// is a root.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of the entry points of the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert_eq!(*graph.roots()[0].borrow().get_value(), 1);
+    /// ```
+    pub fn roots(&self) -> Vec<RefNode<T>> {
+        self.nodes
+            .iter()
+            .filter(|node_ref| node_ref.borrow().live_parents().next().is_none())
+            .map(Rc::clone)
+            .collect()
+    }
+
+    /// Retrieves the nodes without any child, in insertion order.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of the terminal nodes of the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert_eq!(*graph.leaves()[0].borrow().get_value(), 2);
+    /// ```
+    pub fn leaves(&self) -> Vec<RefNode<T>> {
+        self.nodes
+            .iter()
+            .filter(|node_ref| node_ref.borrow().childs.is_empty())
+            .map(Rc::clone)
+            .collect()
+    }
+
+    /// Checks whether the graph forms a tree.
+    ///
+    /// A tree has exactly one root, every other node has exactly one live parent
+    /// and every node is reachable from the root. An empty graph is not considered a tree.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the graph is acyclic, connected and every node has at most one parent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let root = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&root, &child).expect("Failed to add edge");
+    ///
+    /// assert!(graph.is_tree());
+    /// ```
+    pub fn is_tree(&self) -> bool {
+        let root = match self.roots().as_slice() {
+            [root] => Rc::clone(root),
+            _ => return false,
+        };
+
+        if self
+            .nodes
+            .iter()
+            .any(|node_ref| node_ref.borrow().live_parents().count() > 1)
+        {
+            return false;
+        }
+
+        // Every node has at most one parent, so reaching a node twice means there is a cycle.
+        let mut visited = Set::new();
+        let mut stack = vec![root];
+
+        while let Some(node_ref) = stack.pop() {
+            if !visited.insert(Rc::as_ptr(&node_ref)) {
+                return false;
+            }
+
+            stack.extend(node_ref.borrow().childs.iter().map(Rc::clone));
+        }
+
+        visited.len() == self.nodes.len()
+    }
+
+    /// Attaches a string tag to a node.
+    ///
+    /// Tags are labels independent of the node value: they don't take part in equality
+    /// and have no effect on the deduplication done by `get_or_add_node`.
+    /// Adding a tag the node already has does nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node to tag.
+    /// * `tag` - The label to attach.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let node = graph.get_or_add_node(1);
+    /// graph.add_tag(&node, "external");
+    ///
+    /// assert!(node.borrow().has_tag("external"));
+    /// ```
+    pub fn add_tag(&mut self, node: &RefNode<T>, tag: &str) {
+        node.borrow_mut().add_tag(tag);
+    }
+
+    /// Retrieves every node carrying the given tag, in insertion order.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - The label to look for.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of the nodes tagged with `tag`, empty if none are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let node1 = graph.get_or_add_node(1);
+    /// let _node2 = graph.get_or_add_node(2);
+    /// graph.add_tag(&node1, "test-only");
+    ///
+    /// assert_eq!(graph.nodes_with_tag("test-only").len(), 1);
+    /// ```
+    pub fn nodes_with_tag(&self, tag: &str) -> Vec<RefNode<T>> {
+        self.nodes
+            .iter()
+            .filter(|node_ref| node_ref.borrow().has_tag(tag))
+            .map(Rc::clone)
+            .collect()
+    }
+
+    /// Computes the strongly connected components of the graph with Tarjan's algorithm.
+    ///
+    /// A component groups nodes that can all reach each other through the `childs` links.
+    /// In a valid DAG every component is a single node, so a larger component reveals a cycle.
+    /// Components come in reverse topological order: a component is listed before the
+    /// components it can be reached from.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of components, each being a `Vec` of nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// let components = graph.strongly_connected_components();
+    /// assert!(components.iter().all(|component| component.len() == 1));
+    /// ```
+    pub fn strongly_connected_components(&self) -> Vec<Vec<RefNode<T>>> {
+        let adjacency = self.child_indices();
+        let excluded = vec![false; adjacency.len()];
+
+        strongly_connected_indices(&adjacency, &excluded)
+            .into_iter()
+            .map(|component| {
+                component
+                    .into_iter()
+                    .map(|index| Rc::clone(&self.nodes[index]))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Splits the graph into its weakly connected components.
+    ///
+    /// Edges are followed in both directions, so a component groups nodes linked by any chain
+    /// of edges regardless of their orientation. A graph made of a single structure yields a
+    /// single component. Components are ordered by their first node, and the nodes of each
+    /// component keep their insertion order.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of components, each being a `Vec` of nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// graph.add_edges([(1, 2), (3, 2), (4, 5)]).expect("Failed to add edges");
+    ///
+    /// assert_eq!(graph.weakly_connected_components().len(), 2);
+    /// ```
+    pub fn weakly_connected_components(&self) -> Vec<Vec<RefNode<T>>> {
+        let mut neighbours = self.child_indices();
+        for (parent, child, _) in self.weighted_edges() {
+            neighbours[child].push(parent);
+        }
+
+        let mut visited = vec![false; neighbours.len()];
+        let mut components = Vec::new();
+
+        for start in 0..neighbours.len() {
+            if visited[start] {
+                continue;
+            }
+
+            visited[start] = true;
+            let mut component = vec![start];
+            let mut queue = VecDeque::from([start]);
+
+            while let Some(index) = queue.pop_front() {
+                for &neighbour in &neighbours[index] {
+                    if !visited[neighbour] {
+                        visited[neighbour] = true;
+                        component.push(neighbour);
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+
+            component.sort_unstable();
+            components.push(
+                component
+                    .into_iter()
+                    .map(|index| Rc::clone(&self.nodes[index]))
+                    .collect(),
+            );
+        }
+
+        components
+    }
+
+    /// Suggests a set of nodes whose removal would break every cycle of the graph.
+    ///
+    /// Finding the minimum feedback vertex set is NP-hard, so this uses a greedy heuristic:
+    /// the strongly connected components are computed, the node with the highest degree of
+    /// each cyclic component is picked, and the process repeats on the remaining nodes until
+    /// no cycle is left. The result is therefore valid but not guaranteed to be minimal.
+    ///
+    /// Ties between nodes of equal degree are broken by insertion order.
+    /// The graph itself is not modified.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of the suggested nodes, empty if the graph is acyclic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert!(graph.feedback_vertex_candidates().is_empty());
+    /// ```
+    pub fn feedback_vertex_candidates(&self) -> Vec<RefNode<T>> {
+        let adjacency = self.child_indices();
+        let mut excluded = vec![false; adjacency.len()];
+        let mut candidates = Vec::new();
+
+        loop {
+            let mut degrees = vec![0; adjacency.len()];
+            for (index, childs) in adjacency.iter().enumerate() {
+                if excluded[index] {
+                    continue;
+                }
+
+                for &child in childs.iter().filter(|&&child| !excluded[child]) {
+                    degrees[index] += 1;
+                    degrees[child] += 1;
+                }
+            }
+
+            let picked: Vec<usize> = strongly_connected_indices(&adjacency, &excluded)
+                .into_iter()
+                .filter(|component| is_cyclic_component(&adjacency, component))
+                .filter_map(|component| {
+                    component
+                        .into_iter()
+                        .min_by_key(|&index| (Reverse(degrees[index]), index))
+                })
+                .collect();
+
+            if picked.is_empty() {
+                break;
+            }
+
+            for index in picked {
+                excluded[index] = true;
+                candidates.push(Rc::clone(&self.nodes[index]));
+            }
+        }
+
+        candidates
+    }
+
+    /// Releases the memory the graph no longer needs.
+    ///
+    /// The node list, the value index and the edge lists of every node are shrunk to their
+    /// length, and the weak references to parents that have been dropped are pruned.
+    /// This is meant for long-lived graphs that went through a lot of churn.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// for i in 0..100 {
+    ///     graph.get_or_add_node(i);
+    /// }
+    ///
+    /// graph.shrink_to_fit();
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit();
+        #[cfg(feature = "std")]
+        self.index.shrink_to_fit();
+
+        self.nodes
+            .iter()
+            .for_each(|node_ref| node_ref.borrow_mut().shrink_to_fit());
+    }
+
+    /// Sorts the nodes so every parent comes before its childs.
+    ///
+    /// Among the nodes that are ready at the same time, the one inserted first comes first,
+    /// so the order is deterministic for a given construction sequence.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<Vec<RefNode<T>>, CycleError>` containing the ordered nodes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CycleError::Cycle` with the values of the nodes forming the cycle if the graph
+    /// contains one, which can only happen if it was wired outside of `add_edge`.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement the `Display` trait.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let child = graph.get_or_add_node(2);
+    /// let parent = graph.get_or_add_node(1);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// let order = graph.topological_order().expect("The graph is acyclic");
+    /// assert_eq!(*order[0].borrow().get_value(), 1);
+    /// ```
+    pub fn topological_order(&self) -> Result<Vec<RefNode<T>>, CycleError>
+    where
+        T: Display,
+    {
+        let order = self.topological_indices().ok_or_else(|| {
+            CycleError::Cycle(self.cycle_values(&self.find_cycle().unwrap_or_default()))
+        })?;
+
+        Ok(order
+            .into_iter()
+            .map(|index| Rc::clone(&self.nodes[index]))
+            .collect())
+    }
+
+    /// Sorts the values so every parent comes before its childs.
+    ///
+    /// This is the value counterpart of `topological_order`, with the same ordering.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<Vec<T>, CycleError>` containing clones of the ordered values.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CycleError::Cycle` if the graph contains a cycle.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement both the `Clone` and `Display` traits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let child = graph.get_or_add_node(2);
+    /// let parent = graph.get_or_add_node(1);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert_eq!(graph.topological_values().unwrap(), vec![1, 2]);
+    /// ```
+    pub fn topological_values(&self) -> Result<Vec<T>, CycleError>
+    where
+        T: Clone + Display,
+    {
+        Ok(self
+            .topological_order()?
+            .iter()
+            .map(|node_ref| node_ref.borrow().value.clone())
+            .collect())
+    }
+
+    /// Sorts the nodes so every child comes before its parents.
+    ///
+    /// This is the leaves-first order, the exact reverse of `topological_order`,
+    /// suited for teardown sequences where a node must go away before the nodes it hangs from.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<Vec<RefNode<T>>, CycleError>` containing the ordered nodes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CycleError::Cycle` if the graph contains a cycle.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement the `Display` trait.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// let order = graph.reverse_topological_order().expect("The graph is acyclic");
+    /// assert_eq!(*order[0].borrow().get_value(), 2);
+    /// ```
+    pub fn reverse_topological_order(&self) -> Result<Vec<RefNode<T>>, CycleError>
+    where
+        T: Display,
+    {
+        let mut order = self.topological_order()?;
+        order.reverse();
+
+        Ok(order)
+    }
+
+    /// Finds the critical path of the graph, the heaviest path from a root to a leaf.
+    ///
+    /// The length of a path is the sum of the weights of its edges, so with edges added through
+    /// `add_edge` it is the number of edges. The computation is a dynamic programming pass over
+    /// the topological order, in `O(V + E)`. When several paths are equally long, the one ending
+    /// at the leaf inserted first is returned.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec` of the nodes of the path, from the root to the leaf. It is empty if the graph is
+    /// empty or contains a cycle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let node1 = graph.get_or_add_node(1);
+    /// let node2 = graph.get_or_add_node(2);
+    /// let node3 = graph.get_or_add_node(3);
+    ///
+    /// DependencyGraph::add_edge(&node1, &node2).expect("Failed to add edge");
+    /// DependencyGraph::add_edge(&node2, &node3).expect("Failed to add edge");
+    /// DependencyGraph::add_edge(&node1, &node3).expect("Failed to add edge");
+    ///
+    /// assert_eq!(graph.longest_path().len(), 3);
+    /// ```
+    pub fn longest_path(&self) -> Vec<RefNode<T>> {
+        let Some(order) = self.topological_indices() else {
+            return Vec::new();
+        };
+
+        let mut incoming = vec![Vec::new(); self.nodes.len()];
+        for (parent, child, weight) in self.weighted_edges() {
+            incoming[child].push((parent, weight));
+        }
+
+        let mut lengths = vec![0.0; self.nodes.len()];
+        let mut previous = vec![None; self.nodes.len()];
+
+        for &index in &order {
+            for &(parent, weight) in &incoming[index] {
+                let length = lengths[parent] + weight;
+
+                if previous[index].is_none() || length > lengths[index] {
+                    lengths[index] = length;
+                    previous[index] = Some(parent);
+                }
+            }
+        }
+
+        let end = (0..self.nodes.len())
+            .filter(|&index| self.nodes[index].borrow().childs.is_empty())
+            .fold(None, |best: Option<usize>, index| match best {
+                Some(best) if lengths[best] >= lengths[index] => Some(best),
+                _ => Some(index),
+            });
+
+        let mut path = Vec::new();
+        let mut current = end;
+
+        while let Some(index) = current {
+            path.push(Rc::clone(&self.nodes[index]));
+            current = previous[index];
+        }
+
+        path.reverse();
+        path
+    }
+
+    /// Groups the nodes in levels that can be processed one after the other.
+    ///
+    /// Level 0 holds the roots, and every following level holds the nodes whose parents all
+    /// belong to earlier levels. The nodes of a level don't depend on each other, so each level
+    /// can be processed in parallel once the previous ones are done. Within a level, nodes are
+    /// in insertion order.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<Vec<Vec<RefNode<T>>>, CycleError>` containing the levels in processing order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CycleError::Cycle` with the values of the nodes forming the cycle if the graph
+    /// contains one.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement the `Display` trait.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child1 = graph.get_or_add_node(2);
+    /// let child2 = graph.get_or_add_node(3);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child1).expect("Failed to add edge");
+    /// DependencyGraph::add_edge(&parent, &child2).expect("Failed to add edge");
+    ///
+    /// let levels = graph.dependency_levels().expect("The graph is acyclic");
+    /// assert_eq!(levels.len(), 2);
+    /// assert_eq!(levels[1].len(), 2);
+    /// ```
+    pub fn dependency_levels(&self) -> Result<Vec<Vec<RefNode<T>>>, CycleError>
+    where
+        T: Display,
+    {
+        let adjacency = self.child_indices();
+        let mut in_degrees = vec![0; adjacency.len()];
+
+        adjacency
+            .iter()
+            .flatten()
+            .for_each(|&child| in_degrees[child] += 1);
+
+        let mut level: Vec<usize> = (0..adjacency.len())
+            .filter(|&index| in_degrees[index] == 0)
+            .collect();
+
+        let mut levels = Vec::new();
+        let mut placed = 0;
+
+        while !level.is_empty() {
+            let mut next_level = Vec::new();
+
+            for &index in &level {
+                for &child in &adjacency[index] {
+                    in_degrees[child] -= 1;
+
+                    if in_degrees[child] == 0 {
+                        next_level.push(child);
+                    }
+                }
+            }
+
+            next_level.sort_unstable();
+            placed += level.len();
+
+            levels.push(
+                level
+                    .into_iter()
+                    .map(|index| Rc::clone(&self.nodes[index]))
+                    .collect(),
+            );
+            level = next_level;
+        }
+
+        if placed != adjacency.len() {
+            let cycle = self.find_cycle().unwrap_or_default();
+            return Err(CycleError::Cycle(self.cycle_values(&cycle)));
+        }
+
+        Ok(levels)
+    }
+
+    /// Finds the first back-edge met by a depth-first search of the graph.
+    ///
+    /// A back-edge points from a node to one of the nodes currently on the search path,
+    /// so its existence proves the graph has a cycle. The search stops as soon as one is found,
+    /// which makes this the cheapest way to check for a cycle and get an example of it.
+    ///
+    /// The search starts from the nodes in insertion order.
+    ///
+    /// # Returns
+    ///
+    /// `Some((parent, child))` for the first edge closing a cycle, `None` if the graph is acyclic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert!(graph.first_back_edge().is_none());
+    /// ```
+    pub fn first_back_edge(&self) -> Option<(RefNode<T>, RefNode<T>)> {
+        let cycle = self.find_cycle()?;
+
+        Some((
+            Rc::clone(&self.nodes[*cycle.last()?]),
+            Rc::clone(&self.nodes[cycle[0]]),
+        ))
+    }
+
+    /// Checks that the graph holds no cycle.
+    ///
+    /// `add_edge` refuses the edges closing a cycle, but nodes wired by hand or through
+    /// `add_edge_unchecked` are not verified. This runs a full depth-first search from every node,
+    /// tracking the nodes on the current path, and reports the first back-edge it meets.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if the graph is acyclic.
+    ///
+    /// # Errors
+    ///
+    /// Returns `CycleError::Cycle` with the values of the nodes forming the cycle.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement the `Display` trait.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// // SAFETY: the graph has a single edge, which can't close a cycle.
+    /// unsafe { DependencyGraph::add_edge_unchecked(&parent, &child) };
+    ///
+    /// assert!(graph.validate_acyclic().is_ok());
+    /// ```
+    pub fn validate_acyclic(&self) -> Result<(), CycleError>
+    where
+        T: Display,
+    {
+        match self.find_cycle() {
+            Some(cycle) => Err(CycleError::Cycle(self.cycle_values(&cycle))),
+            None => Ok(()),
+        }
+    }
+
+    /// Counts the nodes that must be rebuilt when the given node changes.
+    ///
+    /// These are all the nodes transitively reached by following the `parents` links,
+    /// each counted once even when reached through several paths. The node itself is not counted.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node whose change is being estimated.
+    ///
+    /// # Returns
+    ///
+    /// The number of transitive dependents of `node`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert_eq!(graph.downstream_rebuild_size(&child), 1);
+    /// assert_eq!(graph.downstream_rebuild_size(&parent), 0);
+    /// ```
+    pub fn downstream_rebuild_size(&self, node: &RefNode<T>) -> usize {
+        DependencyGraph::ancestors(node).len()
+    }
+
+    /// Computes the depth of a node, the length of the longest chain of parents above it.
+    ///
+    /// Roots have a depth of 0 and every other node sits one level below its deepest parent,
+    /// so the depth matches the index of the level `dependency_levels` would put the node in.
+    /// Only the ancestors of the node are visited, in topological order. Parents caught in a
+    /// cycle, which can only exist if the graph was wired outside of `add_edge`, are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node to measure.
+    ///
+    /// # Returns
+    ///
+    /// The depth of `node`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// graph.add_edges([(1, 2), (2, 3), (1, 3)]).expect("Failed to add edges");
+    ///
+    /// let node = graph.get_or_add_node(3);
+    /// assert_eq!(graph.depth(&node), 2);
+    /// ```
+    pub fn depth(&self, node: &RefNode<T>) -> usize {
+        let mut nodes = DependencyGraph::ancestors(node);
+        nodes.push(Rc::clone(node));
+
+        let positions: Map<_, _> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node_ref)| (Rc::as_ptr(node_ref), index))
+            .collect();
+
+        // Every live parent of an ancestor is itself an ancestor, so all of them are indexed.
+        let mut childs = vec![Vec::new(); nodes.len()];
+        let mut pending_parents = vec![0; nodes.len()];
+
+        for (index, node_ref) in nodes.iter().enumerate() {
+            for parent_ref in node_ref.borrow().live_parents() {
+                childs[positions[&Rc::as_ptr(&parent_ref)]].push(index);
+                pending_parents[index] += 1;
+            }
+        }
+
+        let mut depths = vec![0; nodes.len()];
+        let mut queue: VecDeque<_> = (0..nodes.len())
+            .filter(|&index| pending_parents[index] == 0)
+            .collect();
+
+        while let Some(index) = queue.pop_front() {
+            for &child in &childs[index] {
+                depths[child] = depths[child].max(depths[index] + 1);
+                pending_parents[child] -= 1;
+
+                if pending_parents[child] == 0 {
+                    queue.push_back(child);
+                }
+            }
+        }
+
+        depths[nodes.len() - 1]
+    }
+
+    /// Precomputes which nodes can be reached from each node.
+    ///
+    /// Every node is walked breadth-first once, so building the closure costs `O(V * (V + E))`
+    /// and up to `O(V²)` memory. In exchange `Reachability::can_reach` answers in constant
+    /// time, where `has_path` walks the graph on every call. Prefer `has_path` for a few
+    /// queries, the closure for many queries on a graph that no longer changes.
+    ///
+    /// # Returns
+    ///
+    /// A `Reachability<T>` snapshot of the current graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// graph.add_edges([(1, 2), (2, 3)]).expect("Failed to add edges");
+    ///
+    /// let node1 = graph.get_or_add_node(1);
+    /// let node3 = graph.get_or_add_node(3);
+    ///
+    /// let reachability = graph.transitive_closure();
+    /// assert!(reachability.can_reach(&node1, &node3));
+    /// assert!(!reachability.can_reach(&node3, &node1));
+    /// ```
+    pub fn transitive_closure(&self) -> Reachability<T> {
+        Reachability::new(&self.nodes)
+    }
+
+    /// Checks that no node of the batch can be reached from another one.
+    ///
+    /// Such a set of mutually independent nodes is an antichain: since none of them depends
+    /// on another, they can all be processed concurrently. A batch holding the same node
+    /// twice is not an antichain.
+    ///
+    /// # Arguments
+    ///
+    /// * `nodes` - The batch of nodes to check.
+    ///
+    /// # Returns
+    ///
+    /// `true` if there is no path between any two nodes of the batch, in either direction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child1 = graph.get_or_add_node(2);
+    /// let child2 = graph.get_or_add_node(3);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child1).expect("Failed to add edge");
+    /// DependencyGraph::add_edge(&parent, &child2).expect("Failed to add edge");
+    ///
+    /// assert!(graph.is_antichain(&[child1.clone(), child2.clone()]));
+    /// assert!(!graph.is_antichain(&[parent, child1]));
+    /// ```
+    pub fn is_antichain(&self, nodes: &[RefNode<T>]) -> bool {
+        let batch: Set<_> = nodes.iter().map(Rc::as_ptr).collect();
+
+        if batch.len() != nodes.len() {
+            return false;
+        }
+
+        nodes.iter().all(|node_ref| {
+            let mut visited = Set::new();
+            let mut stack: Vec<_> = node_ref.borrow().childs.iter().map(Rc::clone).collect();
+
+            while let Some(current) = stack.pop() {
+                if !visited.insert(Rc::as_ptr(&current)) {
+                    continue;
+                }
+
+                if batch.contains(&Rc::as_ptr(&current)) {
+                    return false;
+                }
+
+                stack.extend(current.borrow().childs.iter().map(Rc::clone));
+            }
+
+            true
+        })
+    }
+
+    // Depth-first search stopping at the first back-edge, starting from the nodes in insertion
+    // order. Returns the positions of the nodes forming the cycle, from the target of the
+    // back-edge to its source.
+    fn find_cycle(&self) -> Option<Vec<usize>> {
+        find_cycle_indices(&self.child_indices())
+    }
+
+    // Renders the values of the nodes at the given positions, closing the loop with the first one.
+    fn cycle_values(&self, cycle: &[usize]) -> Vec<String>
+    where
+        T: Display,
+    {
+        values_along_cycle(&self.nodes, cycle)
+    }
+
+    // Kahn's algorithm over the positions in `self.nodes`, ready nodes are taken in insertion order.
+    // Returns `None` if some nodes could not be ordered because of a cycle.
+    fn topological_indices(&self) -> Option<Vec<usize>> {
+        let adjacency = self.child_indices();
+        let mut in_degrees = vec![0; adjacency.len()];
+
+        adjacency
+            .iter()
+            .flatten()
+            .for_each(|&child| in_degrees[child] += 1);
+
+        let mut ready: BinaryHeap<Reverse<usize>> = in_degrees
+            .iter()
+            .enumerate()
+            .filter(|(_, &degree)| degree == 0)
+            .map(|(index, _)| Reverse(index))
+            .collect();
+
+        let mut order = Vec::with_capacity(adjacency.len());
+
+        while let Some(Reverse(index)) = ready.pop() {
+            order.push(index);
+
+            for &child in &adjacency[index] {
+                in_degrees[child] -= 1;
+
+                if in_degrees[child] == 0 {
+                    ready.push(Reverse(child));
+                }
+            }
+        }
+
+        (order.len() == adjacency.len()).then_some(order)
+    }
+
+    /// Removes the edges implied by longer paths, keeping the same reachability.
+    ///
+    /// An edge from a parent to a child is redundant when the child can still be reached
+    /// through another child of the parent, which is what `has_path` checks with the direct
+    /// edge left aside. For an acyclic graph the resulting edge set is the unique minimal one,
+    /// so the redundant edges are collected first and removed together. The weights of the
+    /// removed edges are lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// graph.add_edges([(1, 2), (2, 3), (1, 3)]).expect("Failed to add edges");
+    ///
+    /// graph.transitive_reduction();
+    /// assert_eq!(graph.edge_count(), 2);
+    /// ```
+    pub fn transitive_reduction(&mut self) {
+        let mut redundant = Vec::new();
+
+        for parent_ref in &self.nodes {
+            let parent = parent_ref.borrow();
+
+            for child_ref in &parent.childs {
+                let implied = parent.childs.iter().any(|other_ref| {
+                    !Rc::ptr_eq(other_ref, child_ref)
+                        && DependencyGraph::has_path(other_ref, child_ref)
+                });
+
+                if implied {
+                    redundant.push((Rc::clone(parent_ref), Rc::clone(child_ref)));
+                }
+            }
+        }
+
+        for (parent_ref, child_ref) in redundant {
+            DependencyGraph::remove_edge(&parent_ref, &child_ref);
+        }
+    }
+
+    /// Merges the nodes of the graph holding equal values into a single node.
+    ///
+    /// `get_or_add_node` never creates two nodes for the same value, so a graph built through
+    /// it has nothing to merge. This repairs a node list that ended up with duplicates anyway.
+    /// For each value the node inserted first is kept, and the edges and tags of its
+    /// duplicates are moved onto it. An edge that the kept node already has keeps its weight.
+    /// The duplicates are removed from the graph, left without any edge, and the index is
+    /// rebuilt.
+    ///
+    /// Only the nodes of this graph are merged. Nodes of other graphs linked to a duplicate
+    /// have that link moved onto the kept node, but they are never merged themselves, even
+    /// when they hold an equal value.
+    ///
+    /// The merge is checked before anything is changed, so on error the graph is left untouched.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<usize, AddEdgeError>` containing the number of nodes merged away.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Two duplicates are related, as merging them would create an edge from a node to itself.
+    /// - Merging would close a cycle, the `CyclicRelation` error then lists its values.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement the `Display` trait, and the key `K` must implement
+    /// `ValueKey<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    /// let mut other = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let foreign = other.get_or_add_node(1);
+    ///
+    /// // The node of `other` holds an equal value, but only the nodes of `graph` are merged.
+    /// DependencyGraph::add_edge(&parent, &foreign).expect("Failed to add edge");
+    ///
+    /// assert_eq!(graph.deduplicate().expect("The merge is acyclic"), 0);
+    /// assert!(parent.borrow().has_child(&foreign));
+    /// ```
+    pub fn deduplicate(&mut self) -> Result<usize, AddEdgeError>
+    where
+        T: Display,
+        K: ValueKey<T>,
+    {
+        let mut kept: Map<u64, Vec<usize>> = Map::new();
+        let mut canonical = Vec::with_capacity(self.nodes.len());
+
+        for (position, node_ref) in self.nodes.iter().enumerate() {
+            let value = &node_ref.borrow().value;
+            let bucket = kept.entry(self.hash_value(value)).or_default();
+            let existing = bucket
+                .iter()
+                .find(|&&other| self.same_value(&self.nodes[other].borrow().value, value));
+
+            match existing {
+                Some(&other) => canonical.push(other),
+                None => {
+                    bucket.push(position);
+                    canonical.push(position);
+                }
+            }
+        }
+
+        let merged = (0..self.nodes.len())
+            .filter(|&position| canonical[position] != position)
+            .count();
+
+        if merged == 0 {
+            return Ok(0);
+        }
+
+        // A cycle closed by the merge may go through the nodes of other graphs, so their
+        // descendants are listed after the nodes of this graph, each standing for itself.
+        let mut nodes = self.nodes.clone();
+        let mut positions = self.positions();
+        let mut next = 0;
+
+        while next < nodes.len() {
+            let childs: Vec<RefNode<T>> = nodes[next].borrow().childs.to_vec();
+
+            for child_ref in childs {
+                let position = *positions
+                    .entry(Rc::as_ptr(&child_ref))
+                    .or_insert(nodes.len());
+
+                if position == nodes.len() {
+                    canonical.push(position);
+                    nodes.push(child_ref);
+                }
+            }
+
+            next += 1;
+        }
+
+        // The graph as it would be once merged, to reject the merge before touching any edge.
+        let mut adjacency = vec![Vec::new(); nodes.len()];
+
+        for (position, node_ref) in nodes.iter().enumerate() {
+            for child_ref in &node_ref.borrow().childs {
+                let parent = canonical[position];
+                let child = canonical[positions[&Rc::as_ptr(child_ref)]];
+
+                if parent == child {
+                    return Err(SameNode(nodes[parent].borrow().value.to_string()));
+                }
+
+                adjacency[parent].push(child);
+            }
+        }
+
+        if let Some(cycle) = find_cycle_indices(&adjacency) {
+            return Err(CyclicRelation(values_along_cycle(&nodes, &cycle)));
+        }
+
+        // Foreign parents are not listed, as they can't take part in a cycle through the graph.
+        let kept_for = |node_ref: &RefNode<T>| match positions.get(&Rc::as_ptr(node_ref)) {
+            Some(&position) => Rc::clone(&nodes[canonical[position]]),
+            None => Rc::clone(node_ref),
+        };
+
+        for (position, duplicate) in nodes.iter().enumerate().take(self.nodes.len()) {
+            let kept_ref = &nodes[canonical[position]];
+
+            if Rc::ptr_eq(kept_ref, duplicate) {
+                continue;
+            }
+
+            let (childs, weights, parents) = {
+                let mut duplicate = duplicate.borrow_mut();
+                let tags = mem::take(&mut duplicate.tags);
+                kept_ref.borrow_mut().tags.extend(tags);
+                (
+                    mem::take(&mut duplicate.childs),
+                    mem::take(&mut duplicate.child_weights),
+                    mem::take(&mut duplicate.parents),
+                )
+            };
+
+            for (child_ref, weight) in childs.iter().zip(weights) {
+                child_ref.borrow_mut().remove_parent(duplicate);
+
+                let target = kept_for(child_ref);
+
+                if !kept_ref.borrow().has_child(&target) {
+                    link(kept_ref, &target, weight);
+                }
+            }
+
+            for parent_ref in parents.iter().filter_map(|parent| parent.upgrade()) {
+                let weight = parent_ref
+                    .borrow()
+                    .get_child_weight(duplicate)
+                    .unwrap_or(1.0);
+                parent_ref.borrow_mut().remove_child(duplicate);
+
+                let source = kept_for(&parent_ref);
+
+                if !source.borrow().has_child(kept_ref) {
+                    link(&source, kept_ref, weight);
+                }
+            }
+        }
+
+        let mut is_kept = canonical
+            .iter()
+            .enumerate()
+            .map(|(position, &kept)| position == kept);
+        self.nodes.retain(|_| is_kept.next().unwrap_or(true));
+
+        self.index.clear();
+        for node_ref in &self.nodes {
+            let hash = self.hash_value(&node_ref.borrow().value);
+            self.index
+                .entry(hash)
+                .or_default()
+                .push(Rc::clone(node_ref));
+        }
+
+        Ok(merged)
+    }
+
+    /// Builds a copy of the graph with every edge flipped.
+    ///
+    /// Every parent to child edge becomes a child to parent edge in the new graph, which shares
+    /// nothing with this one. Nodes keep their insertion order and their tags, edges their weight.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<DependencyGraph<T>, AddEdgeError>` containing the reversed graph.
+    ///
+    /// # Errors
+    ///
+    /// Reversing an acyclic graph can't create a cycle, but if this graph was wired outside of
+    /// `add_edge` and holds one, the `CyclicRelation` error is returned.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement both the `Clone` and `Display` traits, and the key `K` must
+    /// implement `ValueKey<T>` and `Clone`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// let reversed = graph.reversed().expect("The graph is acyclic");
+    /// assert_eq!(*reversed.roots()[0].borrow().get_value(), 2);
+    /// ```
+    pub fn reversed(&self) -> Result<DependencyGraph<T, K>, AddEdgeError>
+    where
+        T: Clone + Display,
+        K: ValueKey<T> + Clone,
+    {
+        let mut reversed =
+            DependencyGraph::with_capacity_and_key(self.nodes.len(), self.key.clone());
+        let nodes: Vec<_> = self
+            .nodes
+            .iter()
+            .map(|node_ref| {
+                let node = node_ref.borrow();
+                let new_node = reversed.get_or_add_node(node.value.clone());
+                new_node.borrow_mut().tags = node.tags.clone();
+
+                new_node
+            })
+            .collect();
+
+        for (parent, child, weight) in self.weighted_edges() {
+            DependencyGraph::add_weighted_edge(&nodes[child], &nodes[parent], weight)?;
+        }
+
+        Ok(reversed)
+    }
+
+    /// Merges another graph into this one.
+    ///
+    /// Every node of `other` is folded in through `get_or_add_node`, so a value present in both
+    /// graphs ends up as a single node holding the tags of both. The edges of `other` are then
+    /// re-created with their weight, an edge already present being left as it is.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The graph to merge, consumed by the call.
+    ///
+    /// # Returns
+    ///
+    /// A `Result<(), AddEdgeError>` indicating success or failure.
+    ///
+    /// # Errors
+    ///
+    /// If an edge of `other` would close a cycle with the edges of this graph, the
+    /// `CyclicRelation` error is returned and the merge stops there. The nodes and the edges
+    /// merged before that point are kept.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement both the `Clone` and `Display` traits, and the key `K` must
+    /// implement `ValueKey<T>`. The key of `other` is not used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    /// let mut other = DependencyGraph::new();
+    ///
+    /// let node1 = graph.get_or_add_node(1);
+    /// let node2 = graph.get_or_add_node(2);
+    /// DependencyGraph::add_edge(&node1, &node2).expect("Failed to add edge");
+    ///
+    /// let node2 = other.get_or_add_node(2);
+    /// let node3 = other.get_or_add_node(3);
+    /// DependencyGraph::add_edge(&node2, &node3).expect("Failed to add edge");
+    ///
+    /// graph.extend(other).expect("The merged graph is acyclic");
+    /// assert_eq!(graph.node_count(), 3);
+    /// assert_eq!(graph.edge_count(), 2);
+    /// ```
+    pub fn extend<O>(&mut self, other: DependencyGraph<T, O>) -> Result<(), AddEdgeError>
+    where
+        T: Clone + Display,
+        K: ValueKey<T>,
+    {
+        let nodes: Vec<_> = other
+            .nodes
+            .iter()
+            .map(|node_ref| {
+                let node = node_ref.borrow();
+                let merged = self.get_or_add_node(node.value.clone());
+                merged.borrow_mut().tags.extend(node.tags.iter().cloned());
+
+                merged
+            })
+            .collect();
+
+        for (parent, child, weight) in other.weighted_edges() {
+            DependencyGraph::add_weighted_edge(&nodes[parent], &nodes[child], weight)?;
+        }
+
+        Ok(())
+    }
+
+    /// Extracts the part of the graph a node transitively depends on.
+    ///
+    /// The new graph holds `root` followed by its descendants in breadth-first order, with
+    /// their tags and every edge between them along with its weight. It shares nothing with
+    /// this one, which is left untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The node the subgraph starts from.
+    ///
+    /// # Returns
+    ///
+    /// A new `DependencyGraph<T>` holding `root` and its descendants.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement both the `Clone` and `Display` traits, and the key `K` must
+    /// implement `ValueKey<T>` and `Clone`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let node1 = graph.get_or_add_node(1);
+    /// let node2 = graph.get_or_add_node(2);
+    /// let node3 = graph.get_or_add_node(3);
+    ///
+    /// DependencyGraph::add_edge(&node1, &node2).expect("Failed to add edge");
+    /// DependencyGraph::add_edge(&node2, &node3).expect("Failed to add edge");
+    ///
+    /// let subgraph = graph.subgraph_from(&node2);
+    /// assert_eq!(subgraph.values(), vec![2, 3]);
+    /// ```
+    pub fn subgraph_from(&self, root: &RefNode<T>) -> DependencyGraph<T, K>
+    where
+        T: Clone + Display,
+        K: ValueKey<T> + Clone,
+    {
+        let mut kept = vec![Rc::clone(root)];
+        kept.extend(DependencyGraph::descendants(root));
+
+        let mut subgraph = DependencyGraph::with_capacity_and_key(kept.len(), self.key.clone());
+        let copies: Map<_, _> = kept
+            .iter()
+            .map(|node_ref| {
+                let node = node_ref.borrow();
+                let copy = subgraph.get_or_add_node(node.value.clone());
+                copy.borrow_mut().tags = node.tags.clone();
+                copy.borrow_mut().level = node.level;
+
+                (Rc::as_ptr(node_ref), copy)
+            })
+            .collect();
+
+        // The edges are wired directly, as they already passed the cycle check in this graph,
+        // and the copies have the levels of their originals.
+        for node_ref in &kept {
+            let node = node_ref.borrow();
+            let parent = &copies[&Rc::as_ptr(node_ref)];
+
+            for (child_ref, &weight) in node.childs.iter().zip(&node.child_weights) {
+                let child = &copies[&Rc::as_ptr(child_ref)];
+
+                parent.borrow_mut().add_child(child, weight);
+                child.borrow_mut().add_parent(parent);
+            }
+        }
+
+        subgraph
+    }
+
+    /// Renders the descendants of a node as an indented tree, for terminal output.
+    ///
+    /// Every child is written below its parent with a `├──` or `└──` connector. A node reachable
+    /// through several paths is only expanded the first time, its next occurrences being
+    /// followed by `(see above)`, so diamonds don't blow up the output.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The node at the top of the tree.
+    ///
+    /// # Returns
+    ///
+    /// A `String` holding one line per occurrence of a node.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement the `Display` trait.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// graph.add_edges([(1, 2), (1, 3)]).expect("Failed to add edges");
+    ///
+    /// let root = graph.get_or_add_node(1);
+    /// assert_eq!(graph.print_tree(&root), "1\n├── 2\n└── 3\n");
+    /// ```
+    pub fn print_tree(&self, root: &RefNode<T>) -> String
+    where
+        T: Display,
+    {
+        let mut tree = String::new();
+        let mut visited = Set::new();
+
+        // Each entry holds a node, the prefix of its line and the prefix of its childs' lines.
+        let mut stack = vec![(Rc::clone(root), String::new(), String::new())];
+
+        while let Some((node_ref, line_prefix, child_prefix)) = stack.pop() {
+            let node = node_ref.borrow();
+            tree.push_str(&format!("{line_prefix}{}", node.value));
+
+            if !visited.insert(Rc::as_ptr(&node_ref)) {
+                tree.push_str(" (see above)\n");
+                continue;
+            }
+
+            tree.push('\n');
+
+            let last = node.childs.len().saturating_sub(1);
+            for (index, child_ref) in node.childs.iter().enumerate().rev() {
+                let (connector, indent) = if index == last {
+                    ("└── ", "    ")
+                } else {
+                    ("├── ", "│   ")
+                };
+
+                stack.push((
+                    Rc::clone(child_ref),
+                    format!("{child_prefix}{connector}"),
+                    format!("{child_prefix}{indent}"),
+                ));
+            }
+        }
+
+        tree
+    }
+
+    /// Renders the graph in the Graphviz DOT format.
+    ///
+    /// Every node is identified by its position in the graph (`n0`, `n1`, ...) and labelled
+    /// with its value, quotes and backslashes being escaped. Each edge is written as
+    /// `parent -> child;`. The output can be piped into `dot -Tpng`.
+    ///
+    /// # Returns
+    ///
+    /// A `String` holding a `digraph { ... }` description of the graph.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement the `Display` trait.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::DependencyGraph;
+    /// let mut graph = DependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// DependencyGraph::add_edge(&parent, &child).expect("Failed to add edge");
+    ///
+    /// assert!(graph.to_dot().contains("n0 -> n1;"));
+    /// ```
+    pub fn to_dot(&self) -> String
+    where
+        T: Display,
+    {
+        let mut dot = String::from("digraph {\n");
+
+        for (index, node_ref) in self.nodes.iter().enumerate() {
+            let label = escape_dot_label(&node_ref.borrow().value.to_string());
+            dot.push_str(&format!("    n{index} [label=\"{label}\"];\n"));
+        }
+
+        for (parent, childs) in self.child_indices().iter().enumerate() {
+            for child in childs {
+                dot.push_str(&format!("    n{parent} -> n{child};\n"));
+            }
+        }
+
+        dot.push('}');
+        dot.push('\n');
+
+        dot
+    }
+
+    // Maps every node to its position in `self.nodes`.
+    fn positions(&self) -> Map<*const RefCell<Node<T>>, usize> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node_ref)| (Rc::as_ptr(node_ref), index))
+            .collect()
+    }
+
+    // Lists every edge as the positions of its parent and child in `self.nodes`, with its weight.
+    fn weighted_edges(&self) -> Vec<(usize, usize, f64)> {
+        let positions = self.positions();
+
+        self.nodes
+            .iter()
+            .enumerate()
+            .flat_map(|(parent, node_ref)| {
+                let node = node_ref.borrow();
+
+                node.childs
+                    .iter()
+                    .zip(&node.child_weights)
+                    .filter_map(|(child_ref, &weight)| {
+                        let child = *positions.get(&Rc::as_ptr(child_ref))?;
+                        Some((parent, child, weight))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    // Lists, for every position in `self.nodes`, the positions of its childs.
+    fn child_indices(&self) -> Vec<Vec<usize>> {
+        let positions = self.positions();
+
+        self.nodes
+            .iter()
+            .map(|node_ref| {
+                node_ref
+                    .borrow()
+                    .childs
+                    .iter()
+                    .filter_map(|child_ref| positions.get(&Rc::as_ptr(child_ref)).copied())
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+// The nodes are deep-copied and the edges re-created by position, so the clone shares
+// nothing with the original graph.
+impl<T: Clone, K: Clone> Clone for DependencyGraph<T, K> {
+    fn clone(&self) -> Self {
+        let nodes: Vec<RefNode<T>> = self
+            .nodes
+            .iter()
+            .map(|node_ref| {
+                let original = node_ref.borrow();
+                let mut node = Node::new(original.value.clone());
+                node.tags = original.tags.clone();
+                node.level = original.level;
+
+                Rc::new(RefCell::new(node))
+            })
+            .collect();
+
+        for (parent, child, weight) in self.weighted_edges() {
+            nodes[parent].borrow_mut().add_child(&nodes[child], weight);
+            nodes[child].borrow_mut().add_parent(&nodes[parent]);
+        }
+
+        let positions = self.positions();
+        let index = self
+            .index
+            .iter()
+            .map(|(hash, bucket)| {
+                let bucket = bucket
+                    .iter()
+                    .filter_map(|node_ref| positions.get(&Rc::as_ptr(node_ref)))
+                    .map(|&position| Rc::clone(&nodes[position]))
+                    .collect();
+
+                (*hash, bucket)
+            })
+            .collect();
+
+        DependencyGraph {
+            nodes,
+            index,
+            hash_builder: self.hash_builder.clone(),
+            key: self.key.clone(),
+        }
+    }
+}
+
+// The values are added through `get_or_add_node`, so repeated values collapse into one node.
+impl<T, K: ValueKey<T> + Default> FromIterator<T> for DependencyGraph<T, K> {
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+        let values = values.into_iter();
+        let mut graph = DependencyGraph::with_capacity_and_key(values.size_hint().0, K::default());
+
+        for value in values {
+            graph.get_or_add_node(value);
+        }
+
+        graph
+    }
+}
+
+// The edges are broken first, in both directions, so that each node is only held by the
+// iterator and its value can be moved out.
+impl<T, K> IntoIterator for DependencyGraph<T, K> {
+    type Item = T;
+    type IntoIter = IntoValues<T>;
+
+    fn into_iter(mut self) -> IntoValues<T> {
+        let nodes = self.drain();
+
+        for node_ref in &nodes {
+            let (childs, parents) = {
+                let mut node = node_ref.borrow_mut();
+                node.child_weights.clear();
+                (mem::take(&mut node.childs), mem::take(&mut node.parents))
+            };
+
+            for child_ref in &childs {
+                child_ref.borrow_mut().remove_parent(node_ref);
+            }
+
+            for parent_ref in parents.iter().filter_map(|parent| parent.upgrade()) {
+                parent_ref.borrow_mut().remove_child(node_ref);
+            }
+        }
+
+        IntoValues::new(nodes)
+    }
+}
+
+// Iterating over a borrowed graph yields its nodes in insertion order, like `nodes` does.
+impl<'a, T, K> IntoIterator for &'a DependencyGraph<T, K> {
+    type Item = &'a RefNode<T>;
+    type IntoIter = slice::Iter<'a, RefNode<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.nodes.iter()
+    }
+}
+
+impl<T, K: Default> Default for DependencyGraph<T, K> {
+    fn default() -> Self {
+        DependencyGraph::with_capacity_and_key(0, K::default())
+    }
+}
+
+// Two graphs are equal when they hold the same values linked by the same edges, with the same
+// weights. Insertion order and tags are ignored. Values are only `Eq`, so the nodes are matched
+// by a linear search, and the edges compared once sorted by the matched positions.
+impl<T: Eq, K> PartialEq for DependencyGraph<T, K> {
+    fn eq(&self, other: &Self) -> bool {
+        if self.nodes.len() != other.nodes.len() {
+            return false;
+        }
+
+        let mut matched = vec![false; other.nodes.len()];
+        let mut mapping = Vec::with_capacity(self.nodes.len());
+
+        for node_ref in &self.nodes {
+            let node = node_ref.borrow();
+            let position = other
+                .nodes
+                .iter()
+                .enumerate()
+                .position(|(index, other_ref)| {
+                    !matched[index] && other_ref.borrow().value == node.value
+                });
+
+            let Some(position) = position else {
+                return false;
+            };
+
+            matched[position] = true;
+            mapping.push(position);
+        }
+
+        let mut edges: Vec<_> = self
+            .weighted_edges()
+            .into_iter()
+            .map(|(parent, child, weight)| (mapping[parent], mapping[child], weight))
+            .collect();
+        let mut other_edges = other.weighted_edges();
+
+        let by_position =
+            |a: &(usize, usize, f64), b: &(usize, usize, f64)| (a.0, a.1).cmp(&(b.0, b.1));
+        edges.sort_unstable_by(by_position);
+        other_edges.sort_unstable_by(by_position);
+
+        edges == other_edges
+    }
+}
+
+impl<T: Debug, K> Debug for DependencyGraph<T, K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let nodes: Vec<_> = self
+            .nodes
+            .iter()
+            .map(|node_ref| node_ref.borrow())
+            .collect();
+        let values: Vec<_> = nodes.iter().map(|node| &node.value).collect();
+        let edges: Vec<_> = self
+            .weighted_edges()
+            .into_iter()
+            .map(|(parent, child, weight)| (values[parent], values[child], weight))
+            .collect();
+
+        f.debug_struct("DependencyGraph")
+            .field("values", &values)
+            .field("edges", &edges)
+            .finish()
+    }
+}
+
+fn verify_if_exists_in_parents<T: Display>(
+    parent_ref: &RefNode<T>,
+    child_ref: &RefNode<T>,
+) -> Result<(), AddEdgeError> {
+    if let Some(path) = find_in_parents(parent_ref, child_ref) {
+        // The path goes up from the parent to the child, the new edge would close it.
+        let cycle = path
+            .iter()
+            .rev()
+            .chain(path.last())
+            .map(|node_ref| node_ref.borrow().value.to_string())
+            .collect();
+
+        return Err(CyclicRelation(cycle));
+    }
+
+    Ok(())
+}
+
+// Walks up the ancestry of `start_ref` looking for `searched_ref`, returning the nodes leading
+// to it when found. The walk uses an explicit stack so long chains don't overflow the call stack,
+// and each ancestor is explored only once. Callers rule out self-edges beforehand.
+fn find_in_parents<T>(
+    start_ref: &RefNode<T>,
+    searched_ref: &RefNode<T>,
+) -> Option<Vec<RefNode<T>>> {
+    // Each frame holds a node of the current path and the parents left to explore from it,
+    // stored in reverse so they are popped in order.
+    let pending_parents = |node_ref: &RefNode<T>| -> Vec<RefNode<T>> {
+        let mut parents: Vec<_> = node_ref.borrow().live_parents().collect();
+        parents.reverse();
+        parents
+    };
+
+    let mut visited = Set::from([Rc::as_ptr(start_ref)]);
+    let mut stack = vec![(Rc::clone(start_ref), pending_parents(start_ref))];
+
+    while let Some((_, pending)) = stack.last_mut() {
+        let Some(parent_ref) = pending.pop() else {
+            stack.pop();
+            continue;
+        };
+
+        if !visited.insert(Rc::as_ptr(&parent_ref)) {
+            continue;
+        }
+
+        if Rc::ptr_eq(&parent_ref, searched_ref) {
+            let mut path: Vec<_> = stack.into_iter().map(|(node_ref, _)| node_ref).collect();
+            path.push(parent_ref);
+
+            return Some(path);
+        }
+
+        let parents = pending_parents(&parent_ref);
+        stack.push((parent_ref, parents));
+    }
+
+    None
+}
+
+// Tells whether `searched_ref` can be reached from `start_ref` by following the childs. A node
+// whose level is not below the searched one can't lead to it, so the search doesn't go past it.
+fn reaches_within_levels<T>(start_ref: &RefNode<T>, searched_ref: &RefNode<T>) -> bool {
+    let bound = searched_ref.borrow().level;
+    let mut visited = Set::from([Rc::as_ptr(start_ref)]);
+    let mut stack = vec![Rc::clone(start_ref)];
+
+    while let Some(current) = stack.pop() {
+        if Rc::ptr_eq(&current, searched_ref) {
+            return true;
+        }
+
+        for child_ref in &current.borrow().childs {
+            let promising = Rc::ptr_eq(child_ref, searched_ref) || child_ref.borrow().level < bound;
+            if promising && visited.insert(Rc::as_ptr(child_ref)) {
+                stack.push(Rc::clone(child_ref));
+            }
+        }
+    }
+
+    false
+}
+
+// Wires the edge in both directions, then restores the level order it may break.
+fn link<T>(parent_ref: &RefNode<T>, child_ref: &RefNode<T>, weight: f64) {
+    parent_ref.borrow_mut().add_child(child_ref, weight);
+    child_ref.borrow_mut().add_parent(parent_ref);
+
+    let parent_level = parent_ref.borrow().level;
+    if parent_level < child_ref.borrow().level {
+        return;
+    }
+
+    // A parent without parents of its own can move above the child alone, as happens when a
+    // chain is built from the bottom up. Otherwise the child and its descendants move down.
+    // `raise_levels` walks a descendant again each time its level has to grow, so on a dense
+    // DAG a single edge against the order can cost O(n·edges) rather than O(edges).
+    let parentless = parent_ref.borrow().live_parents().next().is_none();
+    if parentless {
+        let level = child_ref.borrow().level - 1;
+        parent_ref.borrow_mut().level = level;
+    } else {
+        raise_levels(child_ref, parent_level + 1);
+    }
+}
+
+// Raises the level of `start_ref`, then those of its descendants as far as needed to keep each
+// of them below its parents. A node is only walked again when it has to move further down. The
+// nodes on the current path are skipped, so a cycle left by `add_edge_unchecked` can't make
+// this loop forever.
+fn raise_levels<T>(start_ref: &RefNode<T>, level: i64) {
+    start_ref.borrow_mut().level = level;
+
+    let mut on_path = Set::from([Rc::as_ptr(start_ref)]);
+    let mut stack = vec![(Rc::clone(start_ref), 0)];
+
+    while let Some((node_ref, position)) = stack.last_mut() {
+        let (next, level) = {
+            let node = node_ref.borrow();
+            (node.childs.get(*position).map(Rc::clone), node.level + 1)
+        };
+        *position += 1;
+
+        let Some(child_ref) = next else {
+            on_path.remove(&Rc::as_ptr(node_ref));
+            stack.pop();
+            continue;
+        };
+
+        if on_path.contains(&Rc::as_ptr(&child_ref)) || child_ref.borrow().level >= level {
+            continue;
+        }
+
+        child_ref.borrow_mut().level = level;
+        on_path.insert(Rc::as_ptr(&child_ref));
+        stack.push((child_ref, 0));
+    }
+}
+
+// Walks the graph breadth-first from `start`, using `next` to list the neighbours of a node.
+// Every node is yielded once, the start itself is excluded.
+fn breadth_first<T>(
+    start: &RefNode<T>,
+    next: impl Fn(&Node<T>) -> Vec<RefNode<T>>,
+) -> Vec<RefNode<T>> {
+    let mut visited = Set::from([Rc::as_ptr(start)]);
+    let mut queue = VecDeque::from([Rc::clone(start)]);
+    let mut reached = Vec::new();
+
+    while let Some(node_ref) = queue.pop_front() {
+        for neighbour in next(&node_ref.borrow()) {
+            if visited.insert(Rc::as_ptr(&neighbour)) {
+                reached.push(Rc::clone(&neighbour));
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    reached
+}
+
+// Escapes a label so it can be written between double quotes in a DOT file.
+fn escape_dot_label(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+
+    for character in label.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(character),
+        }
+    }
+
+    escaped
+}
+
+// Tarjan's algorithm over an index-based adjacency list, skipping the excluded indices.
+// It is written iteratively so deep graphs don't overflow the stack.
+// Components are returned in reverse topological order.
+fn strongly_connected_indices(adjacency: &[Vec<usize>], excluded: &[bool]) -> Vec<Vec<usize>> {
+    let mut indices: Vec<Option<usize>> = vec![None; adjacency.len()];
+    let mut lowlinks = vec![0; adjacency.len()];
+    let mut on_stack = vec![false; adjacency.len()];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut next_index = 0;
+
+    for start in 0..adjacency.len() {
+        if excluded[start] || indices[start].is_some() {
+            continue;
+        }
+
+        indices[start] = Some(next_index);
+        lowlinks[start] = next_index;
+        next_index += 1;
+        stack.push(start);
+        on_stack[start] = true;
+
+        let mut call_stack = vec![(start, 0)];
+
+        while let Some(&(node, child_position)) = call_stack.last() {
+            if let Some(&child) = adjacency[node].get(child_position) {
+                if let Some(last) = call_stack.last_mut() {
+                    last.1 += 1;
+                }
+
+                if excluded[child] {
+                    continue;
+                }
+
+                match indices[child] {
+                    None => {
+                        indices[child] = Some(next_index);
+                        lowlinks[child] = next_index;
+                        next_index += 1;
+                        stack.push(child);
+                        on_stack[child] = true;
+                        call_stack.push((child, 0));
+                    }
+                    Some(child_index) if on_stack[child] => {
+                        lowlinks[node] = lowlinks[node].min(child_index);
+                    }
+                    Some(_) => {}
+                }
+
+                continue;
+            }
+
+            call_stack.pop();
+
+            if let Some(&(parent, _)) = call_stack.last() {
+                lowlinks[parent] = lowlinks[parent].min(lowlinks[node]);
+            }
+
+            if indices[node] == Some(lowlinks[node]) {
+                let mut component = Vec::new();
+
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+
+                    if member == node {
+                        break;
+                    }
+                }
+
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+// Shared by `cycle_values` and `deduplicate`, which reports cycles over a list of nodes
+// extending past the graph. Looks up each position in `nodes` and repeats the first value at
+// the end, so the rendered path reads as a closed loop.
+fn values_along_cycle<T: Display>(nodes: &[RefNode<T>], cycle: &[usize]) -> Vec<String> {
+    cycle
+        .iter()
+        .chain(cycle.first())
+        .map(|&index| nodes[index].borrow().value.to_string())
+        .collect()
+}
+
+// Depth-first search over an adjacency list stopping at the first back-edge, starting from the
+// lowest position. Returns the positions forming the cycle, from the target of the back-edge
+// to its source.
+fn find_cycle_indices(adjacency: &[Vec<usize>]) -> Option<Vec<usize>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Color {
+        Unvisited,
+        OnPath,
+        Done,
+    }
+
+    let mut colors = vec![Color::Unvisited; adjacency.len()];
+
+    for start in 0..adjacency.len() {
+        if colors[start] != Color::Unvisited {
+            continue;
+        }
+
+        colors[start] = Color::OnPath;
+        let mut stack = vec![(start, 0)];
+
+        while let Some(&(node, child_position)) = stack.last() {
+            let Some(&child) = adjacency[node].get(child_position) else {
+                colors[node] = Color::Done;
+                stack.pop();
+                continue;
+            };
+
+            if let Some(last) = stack.last_mut() {
+                last.1 += 1;
+            }
+
+            match colors[child] {
+                Color::OnPath => {
+                    let cycle_start = stack.iter().position(|&(index, _)| index == child)?;
+
+                    return Some(
+                        stack[cycle_start..]
+                            .iter()
+                            .map(|&(index, _)| index)
+                            .collect(),
+                    );
+                }
+                Color::Unvisited => {
+                    colors[child] = Color::OnPath;
+                    stack.push((child, 0));
+                }
+                Color::Done => {}
+            }
+        }
+    }
+
+    None
+}
+
+// A component is cyclic if it has several nodes, or a single node linked to itself.
+fn is_cyclic_component(adjacency: &[Vec<usize>], component: &[usize]) -> bool {
+    match component {
+        [single] => adjacency[*single].contains(single),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dep_graph() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
+
+        let node1 = node1.borrow();
+        let node2 = node2.borrow();
+        let node3 = node3.borrow();
+        let node4 = node4.borrow();
+
+        assert_eq!(node1.childs.len(), 2);
+        assert_eq!(node1.parents.len(), 0);
+
+        assert_eq!(node2.childs.len(), 1);
+        assert_eq!(node2.parents.len(), 1);
+
+        assert_eq!(node3.childs.len(), 1);
+        assert_eq!(node3.parents.len(), 1);
+
+        assert_eq!(node4.childs.len(), 0);
+        assert_eq!(node4.parents.len(), 2);
+    }
+
+    #[test]
+    fn test_duplicate_edge() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+
+        assert_eq!(node1.borrow().childs.len(), 1);
+        assert_eq!(node2.borrow().parents.len(), 1);
+    }
+
+    #[test]
+    fn test_nodes_and_values() {
+        let mut graph = DependencyGraph::new();
+        let node3 = graph.get_or_add_node(3);
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+
+        let nodes: Vec<_> = graph.nodes().collect();
+        assert_eq!(nodes.len(), 3);
+        assert!(Rc::ptr_eq(&nodes[0], &node3));
+        assert!(Rc::ptr_eq(&nodes[1], &node1));
+        assert!(Rc::ptr_eq(&nodes[2], &node2));
+
+        assert_eq!(graph.values(), vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_counts() {
+        let mut graph = DependencyGraph::new();
+        assert_eq!(graph.node_count(), 0);
+        assert_eq!(graph.edge_count(), 0);
+
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
+
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.edge_count(), 4);
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        graph.add_tag(&node3, "external");
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node3).is_ok());
+
+        let mut cloned = graph.clone();
+        assert_eq!(cloned.node_count(), 3);
+        assert_eq!(cloned.edge_count(), 2);
+        assert_eq!(cloned.nodes_with_tag("external").len(), 1);
+
+        let cloned1 = cloned.get_or_add_node(1);
+        let cloned4 = cloned.get_or_add_node(4);
+        assert!(!Rc::ptr_eq(&cloned1, &node1));
+        assert!(DependencyGraph::add_edge(&cloned1, &cloned4).is_ok());
+
+        assert_eq!(cloned.node_count(), 4);
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(node1.borrow().childs.len(), 1);
+
+        // Edges are rebuilt in the clone, so cycles are still caught there.
+        let cloned3 = cloned.get_or_add_node(3);
+        assert!(DependencyGraph::add_edge(&cloned3, &cloned1).is_err());
+    }
+
+    #[test]
+    fn test_degrees() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node3).is_ok());
+
+        assert_eq!(DependencyGraph::out_degree(&node1), 1);
+        assert_eq!(DependencyGraph::in_degree(&node1), 0);
+        assert_eq!(DependencyGraph::out_degree(&node3), 0);
+        assert_eq!(DependencyGraph::in_degree(&node3), 2);
+
+        let outsider = Rc::new(RefCell::new(Node::new(42)));
+        force_edge(&outsider, &node3);
+        assert_eq!(DependencyGraph::in_degree(&node3), 3);
+
+        drop(outsider);
+        assert_eq!(DependencyGraph::in_degree(&node3), 2);
+    }
+
+    #[test]
+    fn test_weighted_edges() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+
+        assert!(DependencyGraph::add_weighted_edge(&node1, &node2, 2.5).is_ok());
+        assert!(DependencyGraph::add_weighted_edge(&node1, &node2, 4.0).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_weighted_edge(&node3, &node1, 1.0).is_err());
+
+        assert_eq!(DependencyGraph::edge_weight(&node1, &node2), Some(2.5));
+        assert_eq!(DependencyGraph::edge_weight(&node1, &node3), Some(1.0));
+        assert_eq!(DependencyGraph::edge_weight(&node2, &node3), None);
+
+        assert!(DependencyGraph::remove_edge(&node1, &node2));
+        assert_eq!(DependencyGraph::edge_weight(&node1, &node2), None);
+        assert_eq!(DependencyGraph::edge_weight(&node1, &node3), Some(1.0));
+        assert_eq!(node1.borrow().child_weights.len(), 1);
+
+        assert!(DependencyGraph::add_weighted_edge(&node2, &node3, 0.5).is_ok());
+        let cloned = graph.clone();
+        let reversed = graph.reversed().unwrap();
+
+        let weight = |graph: &DependencyGraph<i32>, parent, child| {
+            DependencyGraph::edge_weight(&graph.get_node(&parent)?, &graph.get_node(&child)?)
+        };
+
+        assert_eq!(weight(&cloned, 2, 3), Some(0.5));
+        assert_eq!(weight(&reversed, 3, 2), Some(0.5));
+    }
+
+    #[test]
+    fn test_cyclic_graph_error() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+
+        let _ = DependencyGraph::add_edge(&node1, &node2);
+        let _ = DependencyGraph::add_edge(&node2, &node3);
+
+        assert!(DependencyGraph::add_edge(&node3, &node1).is_err());
+    }
+
+    #[test]
+    fn test_can_add_edge() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node3).is_ok());
+
+        assert!(DependencyGraph::can_add_edge(&node1, &node3));
+        assert!(DependencyGraph::can_add_edge(&node1, &node2));
+        assert!(!DependencyGraph::can_add_edge(&node3, &node1));
+        assert!(!DependencyGraph::can_add_edge(&node2, &node2));
+
+        // Nothing was added by the checks.
+        assert_eq!(node1.borrow().childs.len(), 1);
+        assert!(node1.borrow().parents.is_empty());
+    }
+
+    #[test]
+    fn test_cyclic_graph_error_on_deep_chain() {
+        let mut graph = DependencyGraph::new();
+        let nodes: Vec<_> = (0..100_000).map(|i| graph.get_or_add_node(i)).collect();
+
+        // Wiring from the bottom keeps every ancestry check short while building.
+        for i in (0..nodes.len() - 1).rev() {
+            assert!(DependencyGraph::add_edge(&nodes[i], &nodes[i + 1]).is_ok());
+        }
+
+        let result = DependencyGraph::add_edge(&nodes[nodes.len() - 1], &nodes[0]);
+
+        assert!(matches!(result, Err(CyclicRelation(path)) if path.len() == 100_001));
+    }
+
+    #[test]
+    fn test_cyclic_relation_path() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node4, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node3).is_ok());
+
+        let error = DependencyGraph::add_edge(&node3, &node1).unwrap_err();
+
+        assert!(matches!(&error, CyclicRelation(path) if path == &["1", "2", "3", "1"]));
+        assert_eq!(
+            error.to_string(),
+            "Failed to add node as it is already referenced in its ancestry: 1 -> 2 -> 3 -> 1"
+        );
+    }
+
+    #[test]
+    fn test_add_self_edge() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+
+        let error = DependencyGraph::add_edge(&node1, &node1).unwrap_err();
+        assert!(matches!(&error, SameNode(value) if value == "1"));
+
+        let error = DependencyGraph::add_weighted_edge(&node2, &node2, 2.0).unwrap_err();
+        assert!(matches!(&error, SameNode(value) if value == "2"));
+
+        assert_eq!(graph.edge_count(), 1);
+    }
+
+    #[test]
+    fn test_edge_stress() {
+        let mut graph = DependencyGraph::new();
+        let nodes: Vec<_> = (0..50).map(|value| graph.get_or_add_node(value)).collect();
+
+        // A small linear congruential generator keeps the sequence reproducible.
+        let mut state: u64 = 42;
+        let mut next = |bound: usize| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) as usize % bound
+        };
+
+        for _ in 0..20_000 {
+            let parent = &nodes[next(nodes.len())];
+            let child = &nodes[next(nodes.len())];
+
+            if next(3) == 0 {
+                DependencyGraph::remove_edge(parent, child);
+            } else {
+                let added = DependencyGraph::add_edge(parent, child).is_ok();
+                assert_eq!(added, parent.borrow().has_child(child));
+            }
+        }
+
+        assert!(graph.topological_order().is_ok());
+        assert_eq!(
+            graph.edge_count(),
+            nodes.iter().map(DependencyGraph::in_degree).sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_level_order() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+        let node5 = graph.get_or_add_node(5);
+
+        // Built from the bottom up, then from the top down, so the levels have to move both ways.
+        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node4, &node5).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+
+        // An edge against the levels but closing no cycle moves the whole branch below it down.
+        let node6 = graph.get_or_add_node(6);
+        let node7 = graph.get_or_add_node(7);
+        assert!(DependencyGraph::add_edge(&node6, &node7).is_ok());
+        assert!(DependencyGraph::add_edge(&node4, &node6).is_ok());
+        assert!(matches!(
+            DependencyGraph::add_edge(&node7, &node2),
+            Err(CyclicRelation(path)) if path == ["2", "3", "4", "6", "7", "2"]
+        ));
+
+        for (parent, child, _) in graph.weighted_edges() {
+            assert!(graph.nodes[parent].borrow().level < graph.nodes[child].borrow().level);
+        }
+
+        assert!(matches!(
+            DependencyGraph::add_edge(&node4, &node1),
+            Err(CyclicRelation(path)) if path == ["1", "2", "3", "4", "1"]
+        ));
+        assert!(matches!(
+            DependencyGraph::add_edge(&node7, &node1),
+            Err(CyclicRelation(_))
+        ));
+
+        // Removing an edge shrinks the ancestry again.
+        assert!(DependencyGraph::remove_edge(&node1, &node2));
+        assert!(DependencyGraph::add_edge(&node4, &node1).is_ok());
+
+        assert!(graph.remove_node(&node3));
+        assert!(DependencyGraph::add_edge(&node5, &node2).is_ok());
+        assert!(graph.topological_order().is_ok());
+    }
+
+    #[test]
+    fn test_level_order_on_diamonds() {
+        let mut graph = DependencyGraph::new();
+        let root = graph.get_or_add_node(0);
+        let layers: Vec<Vec<_>> = (1..=6)
+            .map(|layer| {
+                (0..4)
+                    .map(|index| graph.get_or_add_node(layer * 10 + index))
+                    .collect()
+            })
+            .collect();
+
+        // Every node has a parent, so each layer wired from the bottom up pushes down all the
+        // layers already below it, through every diamond they form.
+        for node in layers.iter().flatten() {
+            assert!(DependencyGraph::add_edge(&root, node).is_ok());
+        }
+        for pair in layers.windows(2).rev() {
+            for parent in &pair[0] {
+                for child in &pair[1] {
+                    assert!(DependencyGraph::add_edge(parent, child).is_ok());
+                }
+            }
+        }
+
+        assert_eq!(graph.edge_count(), 24 + 5 * 16);
+        for (parent, child, _) in graph.weighted_edges() {
+            assert!(graph.nodes[parent].borrow().level < graph.nodes[child].borrow().level);
+        }
+
+        assert!(matches!(
+            DependencyGraph::add_edge(&layers[5][0], &layers[0][3]),
+            Err(CyclicRelation(_))
+        ));
+        assert!(graph.topological_order().is_ok());
+    }
+
+    #[test]
+    fn test_find_same_node() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node1bis = graph.get_or_add_node(1);
+
+        assert!(Rc::ptr_eq(&node1, &node1bis));
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let mut graph = DependencyGraph::with_capacity(16);
+        assert!(graph.nodes.capacity() >= 16);
+        #[cfg(feature = "std")]
+        assert!(graph.index.capacity() >= 16);
+
+        let node1 = graph.get_or_add_node(1);
+        assert!(Rc::ptr_eq(&graph.get_or_add_node(1), &node1));
+        assert_eq!(graph.node_count(), 1);
+    }
+
+    #[test]
+    fn test_add_edges() {
+        let mut graph = DependencyGraph::new();
+
+        assert!(graph.add_edges([(1, 2), (2, 3), (1, 3), (1, 2)]).is_ok());
+        assert_eq!(graph.values(), vec![1, 2, 3]);
+        assert_eq!(graph.edge_count(), 3);
+
+        let error = graph.add_edges([(3, 4), (4, 1), (4, 5)]).unwrap_err();
+        assert!(matches!(&error, InBatch { index: 1, parent, child, source }
+                if parent == "4" && child == "1" && matches!(**source, CyclicRelation(_))));
+        assert_eq!(
+            error.to_string(),
+            "Failed to add edge 1 of the batch (4 -> 1): \
+             Failed to add node as it is already referenced in its ancestry: 1 -> 2 -> 3 -> 4 -> 1"
+        );
+
+        // The batch stopped at the failing pair.
+        assert!(graph.contains(&4));
+        assert!(!graph.contains(&5));
+        assert_eq!(graph.edge_count(), 4);
+
+        let error = graph.add_edges([(5, 5)]).unwrap_err();
+        assert!(
+            matches!(&error, InBatch { index: 0, source, .. } if matches!(**source, SameNode(_)))
+        );
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut graph = DependencyGraph::new();
+        assert!(graph.add_edges([(1, 2), (2, 3), (1, 3)]).is_ok());
+        assert!(!graph.is_empty());
+
+        let weak_nodes: Vec<_> = graph.nodes().map(|node| Rc::downgrade(&node)).collect();
+
+        graph.clear();
+        assert!(graph.is_empty());
+        assert_eq!(graph.node_count(), 0);
+        assert_eq!(graph.edge_count(), 0);
+        assert!(!graph.contains(&1));
+
+        // The edges don't keep the nodes alive once the graph lets go of them.
+        assert!(weak_nodes.iter().all(|node| node.upgrade().is_none()));
+
+        let node1 = graph.get_or_add_node(1);
+        assert!(Rc::ptr_eq(&graph.get_or_add_node(1), &node1));
+        assert_eq!(graph.node_count(), 1);
+    }
+
+    #[test]
+    fn test_weakly_connected_components() {
+        let mut graph = DependencyGraph::new();
+        assert!(graph.weakly_connected_components().is_empty());
+
+        assert!(graph
+            .add_edges([(1, 2), (2, 3), (1, 3), (4, 5), (6, 5), (4, 6)])
+            .is_ok());
+        graph.get_or_add_node(7);
+
+        let components: Vec<Vec<i32>> = graph
+            .weakly_connected_components()
+            .iter()
+            .map(|component| component.iter().map(|node| node.borrow().value).collect())
+            .collect();
+
+        assert_eq!(components, vec![vec![1, 2, 3], vec![4, 5, 6], vec![7]]);
+
+        // A single edge between the triangles joins them.
+        assert!(graph.add_edges([(5, 3)]).is_ok());
+        assert_eq!(graph.weakly_connected_components().len(), 2);
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut graph = DependencyGraph::new();
+        assert!(graph.add_edges([(1, 2), (2, 3)]).is_ok());
+
+        let nodes = graph.drain();
+        assert!(graph.is_empty());
+        assert!(!graph.contains(&1));
+        assert_eq!(graph.edge_count(), 0);
+
+        let values: Vec<i32> = nodes.iter().map(|node| node.borrow().value).collect();
+        assert_eq!(values, vec![1, 2, 3]);
+        assert!(nodes[0].borrow().has_child(&nodes[1]));
+        assert!(nodes[1].borrow().has_child(&nodes[2]));
+        assert_eq!(DependencyGraph::ancestors(&nodes[2]).len(), 2);
+
+        // The drained graph can be filled again from scratch.
+        let node1 = graph.get_or_add_node(1);
+        assert!(!Rc::ptr_eq(&node1, &nodes[0]));
+    }
+
+    #[test]
+    fn test_rename_node() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+
+        assert!(graph.rename_node(&node1, 10).is_ok());
+        assert!(Rc::ptr_eq(&graph.get_node(&10).unwrap(), &node1));
+        assert!(!graph.contains(&1));
+        assert!(node1.borrow().has_child(&node2));
+        assert_eq!(graph.values(), vec![10, 2]);
+
+        let error = graph.rename_node(&node1, 2).unwrap_err();
+        assert!(matches!(&error, ValueTaken(value) if value == "2"));
+        assert_eq!(node1.borrow().value, 10);
+
+        // Renaming to the current value is a no-op.
+        assert!(graph.rename_node(&node2, 2).is_ok());
+        assert!(Rc::ptr_eq(&graph.get_or_add_node(2), &node2));
+        assert_eq!(graph.node_count(), 2);
+
+        // A node of another graph is left alone, so that graph keeps finding it.
+        let mut other = DependencyGraph::new();
+        let foreign = other.get_or_add_node(3);
+        let error = graph.rename_node(&foreign, 4).unwrap_err();
+        assert!(matches!(&error, ForeignNode(value) if value == "3"));
+        assert_eq!(foreign.borrow().value, 3);
+        assert!(other.contains(&3));
+        assert!(!graph.contains(&4));
+
+        assert!(graph.remove_node(&node1));
+        assert!(!graph.contains(&10));
+    }
+
+    #[test]
+    fn test_path_to_root() {
+        let mut graph = DependencyGraph::new();
+        assert!(graph.add_edges([(1, 2), (2, 4), (3, 4), (4, 5)]).is_ok());
+
+        let values = |nodes: Vec<RefNode<i32>>| -> Vec<i32> {
+            nodes.iter().map(|node| node.borrow().value).collect()
+        };
+
+        let node5 = graph.get_or_add_node(5);
+        assert_eq!(
+            values(DependencyGraph::path_to_root(&node5)),
+            vec![5, 4, 2, 1]
+        );
+
+        let node1 = graph.get_or_add_node(1);
+        assert_eq!(values(DependencyGraph::path_to_root(&node1)), vec![1]);
+
+        // Once the first parent is removed, the walk goes through the remaining one.
+        let node2 = graph.get_or_add_node(2);
+        assert!(graph.remove_node(&node2));
+        drop(node2);
+        assert_eq!(values(DependencyGraph::path_to_root(&node5)), vec![5, 4, 3]);
+    }
+
+    #[test]
+    fn test_depth() {
+        let mut graph = DependencyGraph::new();
+        assert!(graph
+            .add_edges([(1, 2), (2, 3), (3, 4), (1, 4), (5, 4)])
+            .is_ok());
+
+        let depth = |graph: &mut DependencyGraph<i32>, value| {
+            let node = graph.get_or_add_node(value);
+            graph.depth(&node)
+        };
+
+        assert_eq!(depth(&mut graph, 1), 0);
+        assert_eq!(depth(&mut graph, 5), 0);
+        assert_eq!(depth(&mut graph, 3), 2);
+        assert_eq!(depth(&mut graph, 4), 3);
+
+        let levels = graph.dependency_levels().unwrap();
+        for (level, nodes) in levels.iter().enumerate() {
+            assert!(nodes.iter().all(|node| graph.depth(node) == level));
+        }
+
+        // The walk terminates on a forced cycle.
+        let node1 = graph.get_or_add_node(1);
+        let node3 = graph.get_or_add_node(3);
+        force_edge(&node3, &node1);
+        assert_eq!(depth(&mut graph, 5), 0);
+        assert_eq!(depth(&mut graph, 4), 1);
+    }
+
+    #[test]
+    fn test_add_edge_unchecked() {
+        let mut graph = DependencyGraph::new();
+        let nodes: Vec<_> = (0..4).map(|value| graph.get_or_add_node(value)).collect();
+
+        for pair in nodes.windows(2) {
+            // SAFETY: the edges form a chain, which is acyclic.
+            unsafe { DependencyGraph::add_edge_unchecked(&pair[0], &pair[1]) };
+        }
+        // SAFETY: the edge already exists.
+        unsafe { DependencyGraph::add_edge_unchecked(&nodes[0], &nodes[1]) };
+
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(
+            DependencyGraph::edge_weight(&nodes[2], &nodes[3]),
+            Some(1.0)
+        );
+        assert_eq!(graph.topological_values().unwrap(), vec![0, 1, 2, 3]);
+
+        // The checked version still sees the unchecked edges.
+        assert!(DependencyGraph::add_edge(&nodes[3], &nodes[0]).is_err());
+    }
+
+    #[test]
+    fn test_structural_equality() {
+        let mut graph = DependencyGraph::new();
+        assert!(graph.add_edges([(1, 2), (2, 3), (1, 3)]).is_ok());
+
+        let mut same = DependencyGraph::new();
+        same.get_or_add_node(3);
+        assert!(same.add_edges([(1, 3), (2, 3), (1, 2)]).is_ok());
+        same.add_tag(&same.get_node(&2).unwrap(), "ignored");
+
+        assert_eq!(graph, same);
+        assert_eq!(graph, graph.clone());
+        assert_eq!(DependencyGraph::<i32>::new(), DependencyGraph::new());
+
+        let mut other_edge = DependencyGraph::new();
+        assert!(other_edge.add_edges([(1, 2), (2, 3), (3, 1)]).is_err());
+        assert_ne!(graph, other_edge);
+
+        let mut other_weight = DependencyGraph::new();
+        assert!(other_weight.add_edges([(1, 2), (1, 3)]).is_ok());
+        let node2 = other_weight.get_node(&2).unwrap();
+        let node3 = other_weight.get_node(&3).unwrap();
+        assert!(DependencyGraph::add_weighted_edge(&node2, &node3, 2.0).is_ok());
+        assert_ne!(graph, other_weight);
+
+        let mut other_value = graph.clone();
+        other_value.get_or_add_node(4);
+        assert_ne!(graph, other_value);
+
+        assert_eq!(
+            format!("{graph:?}"),
+            "DependencyGraph { values: [1, 2, 3], edges: [(1, 2, 1.0), (1, 3, 1.0), (2, 3, 1.0)] }"
+        );
+    }
+
+    #[test]
+    fn test_print_tree() {
+        let mut graph = DependencyGraph::new();
+        assert!(graph
+            .add_edges([(1, 2), (1, 3), (2, 4), (2, 5), (3, 4), (4, 6)])
+            .is_ok());
+
+        let root = graph.get_or_add_node(1);
+        let expected = [
+            "1",
+            "├── 2",
+            "│   ├── 4",
+            "│   │   └── 6",
+            "│   └── 5",
+            "└── 3",
+            "    └── 4 (see above)",
+            "",
+        ];
+        assert_eq!(graph.print_tree(&root), expected.join("\n"));
+
+        let leaf = graph.get_or_add_node(6);
+        assert_eq!(graph.print_tree(&leaf), "6\n");
+    }
+
+    #[test]
+    fn test_transitive_reduction() {
+        let mut graph = DependencyGraph::new();
+        assert!(graph.add_edges([(1, 2), (2, 3), (1, 3)]).is_ok());
+
+        graph.transitive_reduction();
+
+        let node1 = graph.get_node(&1).unwrap();
+        let node2 = graph.get_node(&2).unwrap();
+        let node3 = graph.get_node(&3).unwrap();
+        assert!(node1.borrow().has_child(&node2));
+        assert!(node2.borrow().has_child(&node3));
+        assert!(!node1.borrow().has_child(&node3));
+        assert!(DependencyGraph::has_path(&node1, &node3));
+        assert_eq!(graph.edge_count(), 2);
+
+        // Longer chains and diamonds keep their required edges.
+        let mut graph = DependencyGraph::new();
+        assert!(graph
+            .add_edges([
+                (1, 2),
+                (1, 3),
+                (2, 4),
+                (3, 4),
+                (4, 5),
+                (1, 4),
+                (1, 5),
+                (2, 5)
+            ])
+            .is_ok());
+
+        graph.transitive_reduction();
+
+        let mut expected = DependencyGraph::new();
+        assert!(expected
+            .add_edges([(1, 2), (1, 3), (2, 4), (3, 4), (4, 5)])
+            .is_ok());
+        assert_eq!(graph, expected);
+    }
+
+    #[test]
+    fn test_transitive_closure() {
+        let mut graph = DependencyGraph::new();
+        assert!(graph
+            .add_edges([(1, 2), (1, 3), (2, 4), (3, 4), (5, 6)])
+            .is_ok());
+
+        let nodes: Vec<_> = graph.nodes().collect();
+        let reachability = graph.transitive_closure();
+
+        for from in &nodes {
+            for to in &nodes {
+                assert_eq!(
+                    reachability.can_reach(from, to),
+                    DependencyGraph::has_path(from, to)
+                );
+            }
+        }
+
+        // The closure is a snapshot of the graph it was built from.
+        let node4 = graph.get_or_add_node(4);
+        let node5 = graph.get_or_add_node(5);
+        assert!(DependencyGraph::add_edge(&node4, &node5).is_ok());
+        assert!(!reachability.can_reach(&node4, &node5));
+
+        let outsider = Rc::new(RefCell::new(Node::new(42)));
+        assert!(!reachability.can_reach(&outsider, &node4));
+        assert!(reachability.can_reach(&outsider, &outsider));
+    }
+
+    #[test]
+    fn test_get_or_add_node_entry() {
+        let mut graph = DependencyGraph::new();
+
+        let (node1, added) = graph.get_or_add_node_entry(1);
+        assert!(added);
+
+        let (same, added) = graph.get_or_add_node_entry(1);
+        assert!(!added);
+        assert!(Rc::ptr_eq(&node1, &same));
+
+        let (_, added) = graph.get_or_add_node_entry(2);
+        assert!(added);
+        assert_eq!(graph.node_count(), 2);
+
+        assert!(graph.remove_node(&node1));
+        let (_, added) = graph.get_or_add_node_entry(1);
+        assert!(added);
+    }
+
+    #[test]
+    fn test_with_key() {
+        // Neither `Hash` nor `Eq`, only the path is.
+        #[derive(Clone)]
+        struct Meta {
+            path: String,
+            size: f64,
+        }
+
+        impl Display for Meta {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self.path)
+            }
+        }
+
+        let meta = |path: &str, size: f64| Meta {
+            path: path.to_string(),
+            size,
+        };
+        let mut graph = DependencyGraph::with_key(|value: &Meta| value.path.clone());
+
+        let node1 = graph.get_or_add_node(meta("a", 1.0));
+        let (same, added) = graph.get_or_add_node_entry(meta("a", 2.0));
+        assert!(!added);
+        assert!(Rc::ptr_eq(&node1, &same));
+        assert_eq!(node1.borrow().value.size, 1.0);
+
+        let node2 = graph.get_or_add_node(meta("b", 1.0));
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(matches!(
+            DependencyGraph::add_edge(&node2, &node1),
+            Err(CyclicRelation(path)) if path == ["a", "b", "a"]
+        ));
+        assert!(graph.contains(&meta("b", 9.0)));
+        assert!(!graph.contains(&meta("c", 1.0)));
+
+        let error = graph.rename_node(&node2, meta("a", 3.0)).unwrap_err();
+        assert!(matches!(&error, ValueTaken(taken) if taken == "a"));
+        assert!(graph.rename_node(&node2, meta("c", 3.0)).is_ok());
+        assert!(graph.contains(&meta("c", 0.0)));
+        assert_eq!(graph.topological_order().unwrap().len(), 2);
+
+        // Derived graphs keep deduplicating by key.
+        let mut reversed = graph.reversed().unwrap();
+        assert!(!reversed.get_or_add_node_entry(meta("a", 9.0)).1);
+
+        let mut clone = graph.clone();
+        assert!(!clone.get_or_add_node_entry(meta("c", 9.0)).1);
+
+        let mut subgraph = graph.subgraph_from(&node1);
+        assert!(!subgraph.get_or_add_node_entry(meta("c", 9.0)).1);
+
+        assert!(graph.remove_node(&node2));
+        assert!(graph.get_node(&meta("c", 3.0)).is_none());
+    }
+
+    #[test]
+    fn test_get_node() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+
+        assert!(Rc::ptr_eq(&graph.get_node(&1).unwrap(), &node1));
+        assert!(graph.get_node(&2).is_none());
+        assert_eq!(graph.node_count(), 1);
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+
+        assert!(graph.contains(&1));
+        assert!(!graph.contains(&2));
+
+        assert!(graph.remove_node(&node1));
+        assert!(!graph.contains(&1));
+    }
+
+    #[test]
+    fn test_index_follows_removals() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let _node2 = graph.get_or_add_node(2);
+
+        assert_eq!(graph.index.len(), 2);
+
+        assert!(graph.remove_node(&node1));
+        assert_eq!(graph.index.len(), 1);
+
+        let node1bis = graph.get_or_add_node(1);
+        assert!(!Rc::ptr_eq(&node1, &node1bis));
+        assert!(Rc::ptr_eq(&node1bis, &graph.get_or_add_node(1)));
+    }
+
+    #[test]
+    fn test_is_tree() {
+        let mut graph = DependencyGraph::new();
+        assert!(!graph.is_tree());
+
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+
+        let _ = DependencyGraph::add_edge(&node1, &node2);
+        let _ = DependencyGraph::add_edge(&node1, &node3);
+
+        assert!(graph.is_tree());
+
+        let node4 = graph.get_or_add_node(4);
+        assert!(!graph.is_tree());
+
+        let _ = DependencyGraph::add_edge(&node2, &node4);
+        let _ = DependencyGraph::add_edge(&node3, &node4);
+        assert!(!graph.is_tree());
+    }
+
+    #[test]
+    fn test_roots_and_leaves() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
+
+        let values = |nodes: Vec<RefNode<i32>>| -> Vec<i32> {
+            nodes.iter().map(|node| node.borrow().value).collect()
+        };
+
+        assert_eq!(values(graph.roots()), vec![1]);
+        assert_eq!(values(graph.leaves()), vec![2, 4]);
+
+        // A node whose parent was dropped becomes a root.
+        let outsider = Rc::new(RefCell::new(Node::new(42)));
+        force_edge(&outsider, &node1);
+        assert!(graph.roots().is_empty());
+
+        drop(outsider);
+        assert_eq!(values(graph.roots()), vec![1]);
+    }
+
+    #[test]
+    fn test_tags() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+
+        graph.add_tag(&node1, "external");
+        graph.add_tag(&node1, "external");
+        graph.add_tag(&node3, "external");
+        graph.add_tag(&node2, "test-only");
+
+        let external = graph.nodes_with_tag("external");
+        assert_eq!(external.len(), 2);
+        assert!(Rc::ptr_eq(&external[0], &node1));
+        assert!(Rc::ptr_eq(&external[1], &node3));
+        assert_eq!(node1.borrow().get_tags().len(), 1);
+        assert!(graph.nodes_with_tag("missing").is_empty());
+
+        // Tags don't take part in the deduplication.
+        assert!(Rc::ptr_eq(&graph.get_or_add_node(1), &node1));
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
+
+        let components = graph.strongly_connected_components();
+        assert_eq!(components.len(), 4);
+        assert!(Rc::ptr_eq(&components[0][0], &node4));
+
+        force_edge(&node3, &node1);
+
+        let mut sizes: Vec<_> = graph
+            .strongly_connected_components()
+            .iter()
+            .map(Vec::len)
+            .collect();
+        sizes.sort_unstable();
+
+        assert_eq!(sizes, vec![1, 3]);
+    }
+
+    // Wires an edge without the cycle verification, to build graphs `add_edge` would reject.
+    fn force_edge<T>(parent: &RefNode<T>, child: &RefNode<T>) {
+        link(parent, child, 1.0);
+    }
+
+    #[test]
+    fn test_feedback_vertex_candidates() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+        let node5 = graph.get_or_add_node(5);
+
+        assert!(graph.feedback_vertex_candidates().is_empty());
+
+        // Two cycles sharing node 2: 1 -> 2 -> 3 -> 1 and 2 -> 4 -> 2, plus a self-loop on 5.
+        force_edge(&node1, &node2);
+        force_edge(&node2, &node3);
+        force_edge(&node3, &node1);
+        force_edge(&node2, &node4);
+        force_edge(&node4, &node2);
+        force_edge(&node5, &node5);
+
+        let candidates = graph.feedback_vertex_candidates();
+
+        assert_eq!(candidates.len(), 2);
+        assert!(candidates.iter().any(|node| Rc::ptr_eq(node, &node2)));
+        assert!(candidates.iter().any(|node| Rc::ptr_eq(node, &node5)));
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut graph = DependencyGraph::new();
+        let nodes: Vec<_> = (0..5).map(|i| graph.get_or_add_node(i)).collect();
+
+        let outsider = Rc::new(RefCell::new(Node::new(42)));
+        force_edge(&outsider, &nodes[0]);
+        drop(outsider);
+
+        assert!(graph.nodes.capacity() > graph.nodes.len());
+        assert_eq!(nodes[0].borrow().parents.len(), 1);
+
+        graph.shrink_to_fit();
+
+        assert_eq!(graph.nodes.capacity(), graph.nodes.len());
+        assert!(nodes[0].borrow().parents.is_empty());
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let mut graph = DependencyGraph::new();
+        let node4 = graph.get_or_add_node(4);
+        let node3 = graph.get_or_add_node(3);
+        let node2 = graph.get_or_add_node(2);
+        let node1 = graph.get_or_add_node(1);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
+
+        assert_eq!(graph.topological_values().unwrap(), vec![1, 3, 2, 4]);
+
+        force_edge(&node4, &node1);
+        let error = graph.topological_values().unwrap_err();
+        assert!(matches!(&error, CycleError::Cycle(path) if path == &["4", "1", "2", "4"]));
+        assert_eq!(
+            error.to_string(),
+            "The graph contains a cycle: 4 -> 1 -> 2 -> 4"
+        );
+
+        // The error converts to a boxed `Error` for callers propagating with `?`.
+        #[cfg(feature = "std")]
+        {
+            let boxed: Box<dyn std::error::Error> = graph.topological_values().unwrap_err().into();
+            assert!(boxed.to_string().starts_with("The graph contains a cycle"));
+        }
+    }
+
+    #[test]
+    fn test_reverse_topological_order() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node3).is_ok());
+
+        let values: Vec<_> = graph
+            .reverse_topological_order()
+            .unwrap()
+            .iter()
+            .map(|node| node.borrow().value)
+            .collect();
+
+        assert_eq!(values, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_dependency_levels() {
+        let mut graph = DependencyGraph::new();
+        let node5 = graph.get_or_add_node(5);
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node4).is_ok());
+
+        let levels: Vec<Vec<i32>> = graph
+            .dependency_levels()
+            .unwrap()
+            .iter()
+            .map(|level| level.iter().map(|node| node.borrow().value).collect())
+            .collect();
+
+        assert_eq!(levels, vec![vec![5, 1], vec![2, 3], vec![4]]);
+
+        force_edge(&node4, &node5);
+        force_edge(&node5, &node1);
+        assert!(matches!(
+            graph.dependency_levels(),
+            Err(CycleError::Cycle(path)) if path.len() == 5
+        ));
+    }
+
+    #[test]
+    fn test_longest_path() {
+        let mut graph = DependencyGraph::<i32>::new();
+        assert!(graph.longest_path().is_empty());
+
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
         let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+        let node5 = graph.get_or_add_node(5);
 
-        let _ = DependencyGraph::add_edge(&node1, &node2);
-        let _ = DependencyGraph::add_edge(&node2, &node3);
+        let values = |nodes: Vec<RefNode<i32>>| -> Vec<i32> {
+            nodes.iter().map(|node| node.borrow().value).collect()
+        };
 
-        assert!(DependencyGraph::add_edge(&node3, &node1).is_err());
+        assert_eq!(values(graph.longest_path()), vec![1]);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node5, &node4).is_ok());
+
+        assert_eq!(values(graph.longest_path()), vec![1, 2, 3, 4]);
+
+        // A heavy edge outweighs the longer chain.
+        assert!(DependencyGraph::remove_edge(&node5, &node4));
+        assert!(DependencyGraph::add_weighted_edge(&node5, &node4, 10.0).is_ok());
+
+        assert_eq!(values(graph.longest_path()), vec![5, 4]);
+
+        force_edge(&node4, &node1);
+        assert!(graph.longest_path().is_empty());
     }
 
     #[test]
-    fn test_find_same_node() {
+    fn test_extend() {
         let mut graph = DependencyGraph::new();
         let node1 = graph.get_or_add_node(1);
-        let node1bis = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
 
-        assert!(Rc::ptr_eq(&node1, &node1bis));
+        let mut other = DependencyGraph::new();
+        let other2 = other.get_or_add_node(2);
+        let other3 = other.get_or_add_node(3);
+        assert!(DependencyGraph::add_weighted_edge(&other2, &other3, 2.5).is_ok());
+        other.add_tag(&other2, "shared");
+
+        assert!(graph.extend(other).is_ok());
+        assert_eq!(graph.values(), vec![1, 2, 3]);
+        assert_eq!(graph.edge_count(), 2);
+        assert!(node2.borrow().has_tag("shared"));
+
+        let node3 = graph.get_node(&3).expect("The node was merged");
+        assert_eq!(DependencyGraph::edge_weight(&node2, &node3), Some(2.5));
+
+        // Merging the reverse relation would close a cycle.
+        let mut cyclic = DependencyGraph::new();
+        let cyclic3 = cyclic.get_or_add_node(3);
+        let cyclic1 = cyclic.get_or_add_node(1);
+        assert!(DependencyGraph::add_edge(&cyclic3, &cyclic1).is_ok());
+
+        assert!(matches!(
+            graph.extend(cyclic),
+            Err(AddEdgeError::CyclicRelation(_))
+        ));
+        assert!(graph.topological_order().is_ok());
+    }
+
+    #[test]
+    fn test_subgraph_from() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_weighted_edge(&node2, &node3, 3.0).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
+        graph.add_tag(&node3, "tagged");
+
+        let mut subgraph = graph.subgraph_from(&node2);
+        assert_eq!(subgraph.values(), vec![2, 3, 4]);
+        assert_eq!(subgraph.edge_count(), 3);
+        assert!(!subgraph.contains(&1));
+
+        let copy2 = subgraph.get_node(&2).expect("The root is kept");
+        let copy3 = subgraph.get_node(&3).expect("A descendant is kept");
+        assert_eq!(DependencyGraph::edge_weight(&copy2, &copy3), Some(3.0));
+        assert!(copy3.borrow().has_tag("tagged"));
+        assert!(!Rc::ptr_eq(&copy2, &node2));
+
+        // Editing the subgraph leaves the original untouched.
+        let copy5 = subgraph.get_or_add_node(5);
+        assert!(DependencyGraph::add_edge(&copy3, &copy5).is_ok());
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.edge_count(), 4);
+
+        assert_eq!(graph.subgraph_from(&node4).values(), vec![4]);
+    }
+
+    #[test]
+    fn test_first_back_edge() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node3).is_ok());
+        assert!(graph.first_back_edge().is_none());
+
+        force_edge(&node3, &node1);
+
+        let (parent, child) = graph.first_back_edge().expect("The graph has a cycle");
+        assert!(Rc::ptr_eq(&parent, &node3));
+        assert!(Rc::ptr_eq(&child, &node1));
+    }
+
+    // Appends a node holding `value` to the node list without indexing it, which leaves a
+    // duplicate when another node of the graph holds an equal value.
+    fn push_duplicate<T>(graph: &mut DependencyGraph<T>, value: T) -> RefNode<T> {
+        let node = Rc::new(RefCell::new(Node::new(value)));
+        graph.nodes.push(Rc::clone(&node));
+        node
+    }
+
+    #[test]
+    fn test_deduplicate() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let foreign2 = Rc::new(RefCell::new(Node::new(2)));
+        let foreign4 = Rc::new(RefCell::new(Node::new(4)));
+
+        assert_eq!(graph.deduplicate().unwrap(), 0);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &foreign4).is_ok());
+        assert!(DependencyGraph::add_edge(&foreign2, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &foreign2).is_ok());
+
+        let duplicate = push_duplicate(&mut graph, 2);
+        graph.add_tag(&node2, "kept");
+        graph.add_tag(&duplicate, "merged");
+        assert!(DependencyGraph::add_weighted_edge(&node1, &duplicate, 3.0).is_ok());
+        assert!(DependencyGraph::add_weighted_edge(&duplicate, &node3, 2.0).is_ok());
+        assert!(DependencyGraph::add_edge(&duplicate, &foreign4).is_ok());
+
+        assert_eq!(graph.deduplicate().unwrap(), 1);
+        assert_eq!(graph.values(), vec![1, 2, 3]);
+        assert!(Rc::ptr_eq(&graph.get_node(&2).unwrap(), &node2));
+        assert!(node2.borrow().has_tag("kept"));
+        assert!(node2.borrow().has_tag("merged"));
+        assert!(duplicate.borrow().get_tags().is_empty());
+
+        // The existing edge keeps its weight, the moved ones keep theirs.
+        assert_eq!(DependencyGraph::edge_weight(&node1, &node2), Some(1.0));
+        assert_eq!(DependencyGraph::edge_weight(&node2, &node3), Some(2.0));
+        assert!(duplicate.borrow().get_childs().is_empty());
+        assert!(duplicate.borrow().get_parents().is_empty());
+
+        // Foreign nodes are relinked but never merged, even when holding an equal value.
+        assert_eq!(foreign4.borrow().get_parents().len(), 1);
+        assert!(node1.borrow().has_child(&foreign2));
+        assert!(foreign2.borrow().has_child(&node3));
+        assert!(graph.validate_acyclic().is_ok());
+        assert!(!DependencyGraph::can_add_edge(&node3, &node1));
+    }
+
+    #[test]
+    fn test_deduplicate_rejects_cycles() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(0);
+        let node2 = graph.get_or_add_node(2);
+        let foreign = Rc::new(RefCell::new(Node::new(5)));
+
+        // Merging would turn 0 -> 2 -> 5 -> 0' into a cycle, through a node of no graph.
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &foreign).is_ok());
+        let duplicate = push_duplicate(&mut graph, 0);
+        assert!(DependencyGraph::add_edge(&foreign, &duplicate).is_ok());
+
+        let error = graph.deduplicate().unwrap_err();
+        assert!(matches!(&error, CyclicRelation(path) if path == &["0", "2", "5", "0"]));
+        assert!(foreign.borrow().has_child(&duplicate));
+        assert_eq!(graph.node_count(), 3);
+
+        // Merging would turn 0 -> 0' into an edge to itself.
+        assert!(DependencyGraph::remove_edge(&foreign, &duplicate));
+        assert!(DependencyGraph::add_edge(&node1, &duplicate).is_ok());
+
+        let error = graph.deduplicate().unwrap_err();
+        assert!(matches!(&error, SameNode(value) if value == "0"));
+        assert!(node1.borrow().has_child(&duplicate));
+    }
+
+    #[test]
+    fn test_from_iterator() {
+        let graph: DependencyGraph<_> = [3, 1, 3, 2, 1].into_iter().collect();
+
+        assert_eq!(graph.values(), vec![3, 1, 2]);
+        assert_eq!(graph.edge_count(), 0);
+        assert!(graph.get_node(&2).is_some());
+    }
+
+    #[test]
+    fn test_into_iterator() {
+        let mut graph = DependencyGraph::new();
+        assert!(graph.add_edges([(3, 1), (1, 2), (3, 2)]).is_ok());
+        let foreign = Rc::new(RefCell::new(Node::new(4)));
+        assert!(DependencyGraph::add_edge(&foreign, &graph.get_node(&3).unwrap()).is_ok());
+
+        let borrowed: Vec<i32> = (&graph)
+            .into_iter()
+            .map(|node_ref| *node_ref.borrow().get_value())
+            .collect();
+        assert_eq!(borrowed, vec![3, 1, 2]);
+
+        let mut values = Vec::new();
+        for value in graph {
+            values.push(value);
+        }
+
+        assert_eq!(values, vec![3, 1, 2]);
+        assert!(foreign.borrow().get_childs().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "still referenced outside the graph")]
+    fn test_into_iterator_with_shared_node() {
+        let mut graph = DependencyGraph::new();
+        let _node = graph.get_or_add_node(1);
+
+        graph.into_iter().for_each(drop);
+    }
+
+    #[test]
+    fn test_validate_acyclic() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+
+        assert!(graph.validate_acyclic().is_ok());
+
+        force_edge(&node1, &node2);
+        force_edge(&node2, &node3);
+        force_edge(&node1, &node3);
+        assert!(graph.validate_acyclic().is_ok());
+
+        force_edge(&node3, &node4);
+        force_edge(&node4, &node2);
+        let error = graph.validate_acyclic().unwrap_err();
+        assert!(matches!(&error, CycleError::Cycle(path) if path == &["2", "3", "4", "2"]));
+    }
+
+    #[test]
+    fn test_downstream_rebuild_size() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
+
+        assert_eq!(graph.downstream_rebuild_size(&node4), 3);
+        assert_eq!(graph.downstream_rebuild_size(&node2), 1);
+        assert_eq!(graph.downstream_rebuild_size(&node1), 0);
+    }
+
+    #[test]
+    fn test_has_path() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node4).is_ok());
+
+        assert!(DependencyGraph::has_path(&node1, &node4));
+        assert!(DependencyGraph::has_path(&node3, &node3));
+        assert!(!DependencyGraph::has_path(&node3, &node4));
+        assert!(!DependencyGraph::has_path(&node4, &node1));
+    }
+
+    #[test]
+    fn test_is_antichain() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
+
+        assert!(graph.is_antichain(&[]));
+        assert!(graph.is_antichain(&[Rc::clone(&node2), Rc::clone(&node3)]));
+        assert!(!graph.is_antichain(&[Rc::clone(&node4), Rc::clone(&node1)]));
+        assert!(!graph.is_antichain(&[Rc::clone(&node2), Rc::clone(&node2)]));
+    }
+
+    #[test]
+    fn test_descendants_and_ancestors() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+        let node5 = graph.get_or_add_node(5);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node4, &node5).is_ok());
+
+        let values = |nodes: Vec<RefNode<i32>>| -> Vec<i32> {
+            nodes.iter().map(|node| node.borrow().value).collect()
+        };
+
+        assert_eq!(
+            values(DependencyGraph::descendants(&node1)),
+            vec![2, 3, 4, 5]
+        );
+        assert_eq!(values(DependencyGraph::ancestors(&node5)), vec![4, 2, 3, 1]);
+        assert!(DependencyGraph::descendants(&node5).is_empty());
+        assert!(DependencyGraph::ancestors(&node1).is_empty());
+    }
+
+    #[test]
+    fn test_bfs() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+        let node5 = graph.get_or_add_node(5);
+
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node4, &node5).is_ok());
+
+        let values: Vec<_> = DependencyGraph::bfs(&node1)
+            .map(|node| node.borrow().value)
+            .collect();
+
+        assert_eq!(values, vec![1, 3, 2, 4, 5]);
+        assert_eq!(DependencyGraph::bfs(&node5).count(), 1);
+    }
+
+    #[test]
+    fn test_dfs() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
+
+        let values = |order| -> Vec<i32> {
+            DependencyGraph::dfs(&node1, order)
+                .map(|node| node.borrow().value)
+                .collect()
+        };
+
+        assert_eq!(values(DfsOrder::PreOrder), vec![1, 2, 4, 3]);
+        assert_eq!(values(DfsOrder::PostOrder), vec![4, 2, 3, 1]);
+
+        force_edge(&node4, &node1);
+        assert_eq!(values(DfsOrder::PreOrder).len(), 4);
+        assert_eq!(values(DfsOrder::PostOrder).len(), 4);
+    }
+
+    #[test]
+    fn test_reversed() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+
+        let reversed = graph.reversed().unwrap();
+
+        assert_eq!(reversed.values(), vec![1, 2, 3]);
+        assert_eq!(reversed.edge_count(), 2);
+        assert_eq!(reversed.topological_values().unwrap(), vec![2, 3, 1]);
+        assert_eq!(node1.borrow().childs.len(), 2);
+
+        force_edge(&node3, &node1);
+        assert!(matches!(graph.reversed(), Err(CyclicRelation(_))));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node("a");
+        let node2 = graph.get_or_add_node("say \"hi\"");
+        let node3 = graph.get_or_add_node("c");
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+
+        assert_eq!(
+            graph.to_dot(),
+            "digraph {\n    \
+             n0 [label=\"a\"];\n    \
+             n1 [label=\"say \\\"hi\\\"\"];\n    \
+             n2 [label=\"c\"];\n    \
+             n0 -> n1;\n    \
+             n0 -> n2;\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_remove_edge() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+
+        assert!(DependencyGraph::remove_edge(&node1, &node2));
+        assert!(!DependencyGraph::remove_edge(&node1, &node2));
+        assert!(!DependencyGraph::remove_edge(&node2, &node3));
+        assert!(!DependencyGraph::remove_edge(&node1, &node1));
+
+        assert_eq!(node1.borrow().childs.len(), 1);
+        assert!(node2.borrow().parents.is_empty());
+        assert_eq!(node3.borrow().parents.len(), 1);
+
+        // The edge can be added in the other direction now that it is gone.
+        assert!(DependencyGraph::add_edge(&node2, &node1).is_ok());
+    }
+
+    #[test]
+    fn test_remove_node() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node4).is_ok());
+        assert!(DependencyGraph::add_edge(&node3, &node4).is_ok());
+
+        let outsider = Rc::new(RefCell::new(Node::new(42)));
+        force_edge(&outsider, &node4);
+        drop(outsider);
+
+        assert!(graph.remove_node(&node2));
+        assert!(!graph.remove_node(&node2));
+
+        assert_eq!(graph.nodes.len(), 3);
+        assert_eq!(node1.borrow().childs.len(), 1);
+        assert!(Rc::ptr_eq(&node1.borrow().childs[0], &node3));
+
+        let node4 = node4.borrow();
+        assert_eq!(node4.parents.len(), 1);
+        assert!(Rc::ptr_eq(&node4.parents[0].upgrade().unwrap(), &node3));
+
+        let node2 = node2.borrow();
+        assert!(node2.childs.is_empty());
+        assert!(node2.parents.is_empty());
     }
 }
@@ -1,10 +1,22 @@
+use crate::collections::Set;
 use crate::{RefNode, WeakRefNode};
-use std::rc::Rc;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::mem::{self, ManuallyDrop};
+use core::ptr;
 
 pub struct Node<T> {
     pub(crate) value: T,
     pub(crate) childs: Vec<RefNode<T>>,
+    // The weight of the edge towards each child, at the same position as in `childs`.
+    pub(crate) child_weights: Vec<f64>,
     pub(crate) parents: Vec<WeakRefNode<T>>,
+    pub(crate) tags: Set<String>,
+    // A position in a topological order: every parent has a lower level than its childs. An edge
+    // going from a lower to a higher level can't close a cycle, which lets `add_edge` skip the
+    // ancestry walk, and bounds the walk to the levels in between otherwise.
+    pub(crate) level: i64,
 }
 
 impl<T> Node<T> {
@@ -12,7 +24,10 @@ impl<T> Node<T> {
         Node {
             value,
             childs: Vec::new(),
+            child_weights: Vec::new(),
             parents: Vec::new(),
+            tags: Set::new(),
+            level: 0,
         }
     }
 
@@ -27,15 +42,114 @@ impl<T> Node<T> {
     pub fn get_parents(&self) -> &Vec<WeakRefNode<T>> {
         &self.parents
     }
+
+    pub fn get_tags(&self) -> &Set<String> {
+        &self.tags
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(tag)
+    }
+
+    pub fn has_child(&self, child: &RefNode<T>) -> bool {
+        self.child_position(child).is_some()
+    }
+
+    pub fn get_child_weight(&self, child: &RefNode<T>) -> Option<f64> {
+        self.child_position(child)
+            .map(|position| self.child_weights[position])
+    }
+
+    fn child_position(&self, child: &RefNode<T>) -> Option<usize> {
+        self.childs
+            .iter()
+            .position(|existing| Rc::ptr_eq(existing, child))
+    }
+
+    // Parents whose weak reference can still be upgraded, dropped nodes are skipped.
+    pub(crate) fn live_parents(&self) -> impl Iterator<Item = RefNode<T>> + '_ {
+        self.parents.iter().filter_map(|parent| parent.upgrade())
+    }
     
     // The methods here are not exposed as pub so the verification logic can be keeped in the `DependencyGraph` struct.
-    pub(crate) fn add_child(&mut self, child: &RefNode<T>) {
+    pub(crate) fn add_child(&mut self, child: &RefNode<T>, weight: f64) {
         self.childs.push(Rc::clone(child));
+        self.child_weights.push(weight);
     }
 
     pub(crate) fn add_parent(&mut self, parent: &RefNode<T>) {
         self.parents.push(Rc::downgrade(parent));
     }
+
+    // Removes the child if present, returns whether it was found.
+    pub(crate) fn remove_child(&mut self, child: &RefNode<T>) -> bool {
+        let Some(position) = self.child_position(child) else {
+            return false;
+        };
+
+        self.childs.remove(position);
+        self.child_weights.remove(position);
+
+        true
+    }
+
+    // Removes the parent if present, returns whether it was found.
+    // Weak references to dropped parents are pruned along the way.
+    pub(crate) fn remove_parent(&mut self, parent: &RefNode<T>) -> bool {
+        let mut found = false;
+
+        self.parents.retain(|existing| match existing.upgrade() {
+            Some(existing) if Rc::ptr_eq(&existing, parent) => {
+                found = true;
+                false
+            }
+            Some(_) => true,
+            None => false,
+        });
+
+        found
+    }
+
+    pub(crate) fn add_tag(&mut self, tag: &str) {
+        self.tags.insert(tag.to_string());
+    }
+
+    // Drops the weak references to removed parents and releases the unused capacity.
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.parents.retain(|parent| parent.strong_count() > 0);
+        self.parents.shrink_to_fit();
+        self.childs.shrink_to_fit();
+        self.child_weights.shrink_to_fit();
+        #[cfg(feature = "std")]
+        self.tags.shrink_to_fit();
+    }
+
+    // Moves the value out of the node, which the `Drop` impl forbids doing by destructuring.
+    pub(crate) fn into_value(self) -> T {
+        let mut node = ManuallyDrop::new(self);
+        node.childs = Vec::new();
+        node.child_weights = Vec::new();
+        node.parents = Vec::new();
+        node.tags = Set::new();
+
+        // SAFETY: the node is never dropped, so the value is moved out exactly once. The other
+        // fields were replaced above and own no memory, so nothing is leaked.
+        unsafe { ptr::read(&node.value) }
+    }
+}
+
+// Dropping a node drops its childs, which would recurse as deep as the graph. The childs this node
+// solely owns are unwrapped and released in a loop instead, so long chains don't overflow the stack.
+impl<T> Drop for Node<T> {
+    fn drop(&mut self) {
+        let mut pending = mem::take(&mut self.childs);
+
+        while let Some(child) = pending.pop() {
+            if let Ok(child) = Rc::try_unwrap(child) {
+                pending.append(&mut child.into_inner().childs);
+            }
+        }
+    }
 }
 
 // The equality is based on the rule that the `DependencyGraph` will return the same node if the value is the same.
@@ -0,0 +1,62 @@
+use crate::collections::{Map, Set};
+use crate::node::Node;
+use crate::traversal::Bfs;
+use crate::{RefNode, WeakRefNode};
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+/// The transitive closure of a graph, answering reachability queries in constant time.
+///
+/// Created by `DependencyGraph::transitive_closure`. Every node is stored with the set of the
+/// nodes it can reach, which takes up to `O(V²)` memory where `DependencyGraph::has_path`
+/// needs none but walks the graph on each call. The closure is a snapshot: edges added or
+/// removed afterwards are not reflected.
+pub struct Reachability<T> {
+    // Holding a weak reference keeps each allocation alive, so the addresses used as keys
+    // can't be reused by nodes created later.
+    anchors: Vec<WeakRefNode<T>>,
+    reachable: Map<*const RefCell<Node<T>>, Set<*const RefCell<Node<T>>>>,
+}
+
+impl<T> Reachability<T> {
+    pub(crate) fn new(nodes: &[RefNode<T>]) -> Reachability<T> {
+        let reachable = nodes
+            .iter()
+            .map(|node_ref| {
+                let descendants = Bfs::new(node_ref)
+                    .skip(1)
+                    .map(|descendant| Rc::as_ptr(&descendant))
+                    .collect();
+
+                (Rc::as_ptr(node_ref), descendants)
+            })
+            .collect();
+
+        Reachability {
+            anchors: nodes.iter().map(Rc::downgrade).collect(),
+            reachable,
+        }
+    }
+
+    /// Checks whether a node could be reached from another when the closure was computed.
+    ///
+    /// Like `DependencyGraph::has_path`, a node is always reachable from itself. A node that
+    /// wasn't part of the graph reaches nothing else.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The node to start from.
+    /// * `to` - The node to look for.
+    ///
+    /// # Returns
+    ///
+    /// `true` if there was a path from `from` to `to`.
+    pub fn can_reach(&self, from: &RefNode<T>, to: &RefNode<T>) -> bool {
+        Rc::ptr_eq(from, to)
+            || self
+                .reachable
+                .get(&Rc::as_ptr(from))
+                .is_some_and(|reachable| reachable.contains(&Rc::as_ptr(to)))
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{DependencyGraph, RefNode, ValueKey};
+use serde::de::Error as _;
+use serde::ser::{SerializeSeq, SerializeStruct};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Display;
+
+// The serialized form of a graph: the values in insertion order, and the edges as the positions
+// of their parent and child in that list, followed by their weight.
+#[derive(Deserialize)]
+#[serde(rename = "DependencyGraph")]
+struct SerializedGraph<T> {
+    values: Vec<T>,
+    edges: Vec<(usize, usize, f64)>,
+}
+
+// Serializes the values of the nodes without cloning them out of their `RefCell`.
+struct Values<'a, T>(&'a [RefNode<T>]);
+
+impl<T: Serialize> Serialize for Values<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut sequence = serializer.serialize_seq(Some(self.0.len()))?;
+
+        for node_ref in self.0 {
+            sequence.serialize_element(&node_ref.borrow().value)?;
+        }
+
+        sequence.end()
+    }
+}
+
+impl<T: Serialize, K> Serialize for DependencyGraph<T, K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let edges = self.weighted_edges();
+
+        let mut state = serializer.serialize_struct("DependencyGraph", 2)?;
+        state.serialize_field("values", &Values(&self.nodes))?;
+        state.serialize_field("edges", &edges)?;
+        state.end()
+    }
+}
+
+// The graph is rebuilt through `get_or_add_node` and `add_edge`, so a serialized form holding a
+// cycle is rejected with the `AddEdgeError` message.
+impl<'de, T, K> Deserialize<'de> for DependencyGraph<T, K>
+where
+    T: Deserialize<'de> + Display,
+    K: ValueKey<T> + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let serialized = SerializedGraph::<T>::deserialize(deserializer)?;
+
+        let mut graph = DependencyGraph::default();
+        let nodes: Vec<_> = serialized
+            .values
+            .into_iter()
+            .map(|value| graph.get_or_add_node(value))
+            .collect();
+
+        for (parent, child, weight) in serialized.edges {
+            let (Some(parent_ref), Some(child_ref)) = (nodes.get(parent), nodes.get(child)) else {
+                return Err(D::Error::custom(format!(
+                    "edge ({parent}, {child}) refers to a missing node"
+                )));
+            };
+
+            DependencyGraph::add_weighted_edge(parent_ref, child_ref, weight)
+                .map_err(D::Error::custom)?;
+        }
+
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut graph = DependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+        let node4 = graph.get_or_add_node(4);
+
+        assert!(DependencyGraph::add_edge(&node1, &node2).is_ok());
+        assert!(DependencyGraph::add_edge(&node1, &node3).is_ok());
+        assert!(DependencyGraph::add_edge(&node2, &node4).is_ok());
+        assert!(DependencyGraph::add_weighted_edge(&node3, &node4, 2.5).is_ok());
+
+        let json = serde_json::to_string(&graph).unwrap();
+        assert_eq!(
+            json,
+            r#"{"values":[1,2,3,4],"edges":[[0,1,1.0],[0,2,1.0],[1,3,1.0],[2,3,2.5]]}"#
+        );
+
+        let restored: DependencyGraph<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, graph);
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+    }
+
+    #[test]
+    fn test_cyclic_edges_are_rejected() {
+        let json = r#"{"values":[1,2],"edges":[[0,1,1.0],[1,0,1.0]]}"#;
+        let Err(error) = serde_json::from_str::<DependencyGraph<i32>>(json) else {
+            panic!("The cyclic edges should have been rejected");
+        };
+
+        assert!(error.to_string().contains("1 -> 2 -> 1"));
+    }
+
+    #[test]
+    fn test_missing_node_is_rejected() {
+        let json = r#"{"values":[1],"edges":[[0,1,1.0]]}"#;
+
+        assert!(serde_json::from_str::<DependencyGraph<i32>>(json).is_err());
+    }
+}
@@ -0,0 +1,395 @@
+use crate::AddEdgeError;
+use crate::AddEdgeError::{CyclicRelation, SameNode};
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::hash::{BuildHasher, Hash};
+use std::sync::{Arc, Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, Weak};
+
+type SyncRefNode<T> = Arc<RwLock<SyncNode<T>>>;
+type WeakSyncRefNode<T> = Weak<RwLock<SyncNode<T>>>;
+
+/// A node of a `SyncDependencyGraph`, the thread-safe counterpart of `Node`.
+pub struct SyncNode<T> {
+    pub(crate) value: T,
+    pub(crate) childs: Vec<SyncRefNode<T>>,
+    pub(crate) parents: Vec<WeakSyncRefNode<T>>,
+}
+
+impl<T> SyncNode<T> {
+    fn new(value: T) -> SyncNode<T> {
+        SyncNode {
+            value,
+            childs: Vec::new(),
+            parents: Vec::new(),
+        }
+    }
+
+    pub fn get_value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn get_childs(&self) -> &Vec<SyncRefNode<T>> {
+        &self.childs
+    }
+
+    pub fn get_parents(&self) -> &Vec<WeakSyncRefNode<T>> {
+        &self.parents
+    }
+}
+
+// Same as for `Node`: childs solely owned are released in a loop so long chains don't overflow
+// the stack when dropped.
+impl<T> Drop for SyncNode<T> {
+    fn drop(&mut self) {
+        let mut pending = std::mem::take(&mut self.childs);
+
+        while let Some(child) = pending.pop() {
+            if let Ok(child) = Arc::try_unwrap(child) {
+                let mut child = child.into_inner().unwrap_or_else(PoisonError::into_inner);
+                pending.append(&mut child.childs);
+            }
+        }
+    }
+}
+
+// A panic while holding a lock doesn't leave a node half updated, so poisoning is ignored.
+fn read<T>(node: &SyncRefNode<T>) -> RwLockReadGuard<'_, SyncNode<T>> {
+    node.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<T>(node: &SyncRefNode<T>) -> RwLockWriteGuard<'_, SyncNode<T>> {
+    node.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+struct Nodes<T> {
+    nodes: Vec<SyncRefNode<T>>,
+    // Same hash buckets as `DependencyGraph`, guarded by the same lock as the node list.
+    index: HashMap<u64, Vec<SyncRefNode<T>>>,
+}
+
+/// A thread-safe dependency graph.
+///
+/// `SyncDependencyGraph<T>` offers the same model as `DependencyGraph<T>`, built on
+/// `Arc<RwLock<..>>` instead of `Rc<RefCell<..>>`, so the graph and its nodes are `Send`
+/// and `Sync` when `T` is. Its methods take `&self` and can be called from several threads.
+///
+/// A node lock is never held while another one is taken, so concurrent operations can't
+/// deadlock. Edge insertions are serialized by the graph so two concurrent `add_edge` calls
+/// can't create a cycle together.
+pub struct SyncDependencyGraph<T> {
+    nodes: RwLock<Nodes<T>>,
+    edges_lock: Mutex<()>,
+    hash_builder: RandomState,
+}
+
+impl<T> SyncDependencyGraph<T> {
+    /// Creates a new, empty `SyncDependencyGraph<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::SyncDependencyGraph;
+    /// let graph: SyncDependencyGraph<i32> = SyncDependencyGraph::new();
+    /// ```
+    pub fn new() -> SyncDependencyGraph<T> {
+        SyncDependencyGraph {
+            nodes: RwLock::new(Nodes {
+                nodes: Vec::new(),
+                index: HashMap::new(),
+            }),
+            edges_lock: Mutex::new(()),
+            hash_builder: RandomState::new(),
+        }
+    }
+
+    /// Retrieves an existing node with the given value or adds a new node if it doesn't exist.
+    ///
+    /// Concurrent calls with equal values all get the same node.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to search for or add to the graph.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement both the `Hash` and `Eq` traits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::SyncDependencyGraph;
+    /// let graph = SyncDependencyGraph::new();
+    ///
+    /// let node = graph.get_or_add_node(42);
+    /// ```
+    pub fn get_or_add_node(&self, value: T) -> SyncRefNode<T>
+    where
+        T: Hash + Eq,
+    {
+        let hash = self.hash_builder.hash_one(&value);
+
+        let fetch_existing = |nodes: &Nodes<T>| {
+            nodes
+                .index
+                .get(&hash)?
+                .iter()
+                .find(|node_ref| read(node_ref).value == value)
+                .map(Arc::clone)
+        };
+
+        if let Some(node) =
+            fetch_existing(&self.nodes.read().unwrap_or_else(PoisonError::into_inner))
+        {
+            return node;
+        }
+
+        let mut nodes = self.nodes.write().unwrap_or_else(PoisonError::into_inner);
+
+        // Another thread may have added the value between the two locks.
+        if let Some(node) = fetch_existing(&nodes) {
+            return node;
+        }
+
+        let ref_node = Arc::new(RwLock::new(SyncNode::new(value)));
+
+        nodes.nodes.push(Arc::clone(&ref_node));
+        nodes
+            .index
+            .entry(hash)
+            .or_default()
+            .push(Arc::clone(&ref_node));
+
+        ref_node
+    }
+
+    /// Adds an edge between two nodes in the graph.
+    ///
+    /// Adding an edge that already exists does nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent_ref` - A reference to the parent node.
+    /// * `child_ref` - A reference to the child node.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The child node already exists in the parent's ancestry (to prevent cycles).
+    /// - An attempt is made to add an edge from a node to itself.
+    ///
+    /// # Type Constraints
+    ///
+    /// The type `T` must implement the `Display` trait.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dependency_graph::SyncDependencyGraph;
+    /// let graph = SyncDependencyGraph::new();
+    ///
+    /// let parent = graph.get_or_add_node(1);
+    /// let child = graph.get_or_add_node(2);
+    ///
+    /// graph.add_edge(&parent, &child).expect("Failed to add edge");
+    /// ```
+    pub fn add_edge(
+        &self,
+        parent_ref: &SyncRefNode<T>,
+        child_ref: &SyncRefNode<T>,
+    ) -> Result<(), AddEdgeError>
+    where
+        T: Display,
+    {
+        if Arc::ptr_eq(parent_ref, child_ref) {
+            return Err(SameNode(read(parent_ref).value.to_string()));
+        }
+
+        let _edges_guard = self
+            .edges_lock
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let already_linked = read(parent_ref)
+            .childs
+            .iter()
+            .any(|existing| Arc::ptr_eq(existing, child_ref));
+
+        if already_linked {
+            return Ok(());
+        }
+
+        if let Some(path) = find_in_parents(parent_ref, child_ref) {
+            // The path goes up from the parent to the child, the new edge would close it.
+            let cycle = path
+                .iter()
+                .rev()
+                .chain(path.last())
+                .map(|node_ref| read(node_ref).value.to_string())
+                .collect();
+
+            return Err(CyclicRelation(cycle));
+        }
+
+        write(parent_ref).childs.push(Arc::clone(child_ref));
+        write(child_ref).parents.push(Arc::downgrade(parent_ref));
+
+        Ok(())
+    }
+
+    /// Counts the nodes of the graph.
+    pub fn node_count(&self) -> usize {
+        self.nodes
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .nodes
+            .len()
+    }
+
+    /// Counts the parent to child edges of the graph.
+    pub fn edge_count(&self) -> usize {
+        self.nodes
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .nodes
+            .iter()
+            .map(|node_ref| read(node_ref).childs.len())
+            .sum()
+    }
+}
+
+impl<T> Default for SyncDependencyGraph<T> {
+    fn default() -> Self {
+        SyncDependencyGraph::new()
+    }
+}
+
+// Walks up the ancestry of `start_ref` looking for `searched_ref`, returning the nodes leading
+// to it when found. Each node is only locked long enough to copy its parents.
+fn find_in_parents<T>(
+    start_ref: &SyncRefNode<T>,
+    searched_ref: &SyncRefNode<T>,
+) -> Option<Vec<SyncRefNode<T>>> {
+    let pending_parents = |node_ref: &SyncRefNode<T>| -> Vec<SyncRefNode<T>> {
+        let mut parents: Vec<_> = read(node_ref)
+            .parents
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect();
+        parents.reverse();
+        parents
+    };
+
+    let mut visited = HashSet::from([Arc::as_ptr(start_ref)]);
+    let mut stack = vec![(Arc::clone(start_ref), pending_parents(start_ref))];
+
+    while let Some((_, pending)) = stack.last_mut() {
+        let Some(parent_ref) = pending.pop() else {
+            stack.pop();
+            continue;
+        };
+
+        if !visited.insert(Arc::as_ptr(&parent_ref)) {
+            continue;
+        }
+
+        if Arc::ptr_eq(&parent_ref, searched_ref) {
+            let mut path: Vec<_> = stack.into_iter().map(|(node_ref, _)| node_ref).collect();
+            path.push(parent_ref);
+
+            return Some(path);
+        }
+
+        let parents = pending_parents(&parent_ref);
+        stack.push((parent_ref, parents));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_sync_graph_is_send_and_sync() {
+        fn assert_send_sync<S: Send + Sync>() {}
+
+        assert_send_sync::<SyncDependencyGraph<i32>>();
+        assert_send_sync::<SyncRefNode<i32>>();
+    }
+
+    #[test]
+    fn test_concurrent_get_or_add_node() {
+        let graph = SyncDependencyGraph::new();
+
+        let nodes: Vec<Vec<SyncRefNode<i32>>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| (0..100).map(|i| graph.get_or_add_node(i)).collect()))
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        assert_eq!(graph.node_count(), 100);
+
+        for thread_nodes in &nodes[1..] {
+            for (node, first) in thread_nodes.iter().zip(&nodes[0]) {
+                assert!(Arc::ptr_eq(node, first));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sync_add_edge() {
+        let graph = SyncDependencyGraph::new();
+        let node1 = graph.get_or_add_node(1);
+        let node2 = graph.get_or_add_node(2);
+        let node3 = graph.get_or_add_node(3);
+
+        assert!(graph.add_edge(&node1, &node2).is_ok());
+        assert!(graph.add_edge(&node2, &node3).is_ok());
+        assert!(graph.add_edge(&node2, &node3).is_ok());
+        assert_eq!(graph.edge_count(), 2);
+
+        assert!(matches!(graph.add_edge(&node1, &node1), Err(SameNode(_))));
+        assert!(matches!(
+            graph.add_edge(&node3, &node1),
+            Err(CyclicRelation(path)) if path == ["1", "2", "3", "1"]
+        ));
+    }
+
+    #[test]
+    fn test_concurrent_add_edge_keeps_graph_acyclic() {
+        let graph = SyncDependencyGraph::new();
+        let nodes: Vec<_> = (0..20).map(|i| graph.get_or_add_node(i)).collect();
+
+        // Every thread tries to link each pair in its own direction, only one direction can win.
+        thread::scope(|scope| {
+            for reversed in [false, true] {
+                let graph = &graph;
+                let nodes = &nodes;
+
+                scope.spawn(move || {
+                    for i in 0..nodes.len() {
+                        for j in 0..nodes.len() {
+                            let (parent, child) = if reversed { (j, i) } else { (i, j) };
+                            let _ = graph.add_edge(&nodes[parent], &nodes[child]);
+                        }
+                    }
+                });
+            }
+        });
+
+        // No child can also be found in the ancestry of its parent.
+        for node in &nodes {
+            for child in &read(node).childs {
+                assert!(find_in_parents(node, child).is_none());
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use crate::collections::Set;
+use crate::node::Node;
+use crate::RefNode;
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+/// A breadth-first iterator over the nodes reachable from a starting node.
+///
+/// Created by `DependencyGraph::bfs`. The starting node is yielded first, then the nodes
+/// following the `childs` links level by level. Each node is yielded once, even when it is
+/// reachable through several paths.
+pub struct Bfs<T> {
+    frontier: VecDeque<RefNode<T>>,
+    visited: Set<*const RefCell<Node<T>>>,
+}
+
+impl<T> Bfs<T> {
+    pub(crate) fn new(start: &RefNode<T>) -> Bfs<T> {
+        Bfs {
+            frontier: VecDeque::from([Rc::clone(start)]),
+            visited: Set::from([Rc::as_ptr(start)]),
+        }
+    }
+}
+
+impl<T> Iterator for Bfs<T> {
+    type Item = RefNode<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node_ref = self.frontier.pop_front()?;
+
+        for child_ref in &node_ref.borrow().childs {
+            if self.visited.insert(Rc::as_ptr(child_ref)) {
+                self.frontier.push_back(Rc::clone(child_ref));
+            }
+        }
+
+        Some(node_ref)
+    }
+}
+
+/// The moment a node is yielded by a `Dfs` iterator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DfsOrder {
+    /// A node is yielded before its childs.
+    PreOrder,
+    /// A node is yielded after all its childs, which gives a teardown sequence.
+    PostOrder,
+}
+
+/// A depth-first iterator over the nodes reachable from a starting node.
+///
+/// Created by `DependencyGraph::dfs`. Each node is yielded once, and nodes already visited
+/// are skipped so a cycle can't cause an infinite loop.
+pub struct Dfs<T> {
+    order: DfsOrder,
+    // The current path, each node with the position of its next child to explore.
+    stack: Vec<(RefNode<T>, usize)>,
+    visited: Set<*const RefCell<Node<T>>>,
+    pending_start: Option<RefNode<T>>,
+}
+
+impl<T> Dfs<T> {
+    pub(crate) fn new(start: &RefNode<T>, order: DfsOrder) -> Dfs<T> {
+        Dfs {
+            order,
+            stack: vec![(Rc::clone(start), 0)],
+            visited: Set::from([Rc::as_ptr(start)]),
+            pending_start: (order == DfsOrder::PreOrder).then(|| Rc::clone(start)),
+        }
+    }
+}
+
+impl<T> Iterator for Dfs<T> {
+    type Item = RefNode<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(start) = self.pending_start.take() {
+            return Some(start);
+        }
+
+        loop {
+            let (node_ref, position) = self.stack.last_mut()?;
+            let child_ref = node_ref.borrow().childs.get(*position).map(Rc::clone);
+            *position += 1;
+
+            match child_ref {
+                Some(child_ref) => {
+                    if !self.visited.insert(Rc::as_ptr(&child_ref)) {
+                        continue;
+                    }
+
+                    self.stack.push((Rc::clone(&child_ref), 0));
+
+                    if self.order == DfsOrder::PreOrder {
+                        return Some(child_ref);
+                    }
+                }
+                None => {
+                    let (node_ref, _) = self.stack.pop()?;
+
+                    if self.order == DfsOrder::PostOrder {
+                        return Some(node_ref);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An iterator moving the values out of a graph.
+///
+/// Created by the `IntoIterator` impl of `DependencyGraph`. The values are yielded in insertion
+/// order, the edges of the graph having been removed beforehand.
+///
+/// # Panics
+///
+/// Panics when reaching a node that is still referenced outside the graph, through a `RefNode`
+/// kept by the caller or an edge from a node of another graph, as its value can't be moved out.
+pub struct IntoValues<T> {
+    nodes: vec::IntoIter<RefNode<T>>,
+}
+
+impl<T> IntoValues<T> {
+    pub(crate) fn new(nodes: Vec<RefNode<T>>) -> IntoValues<T> {
+        IntoValues {
+            nodes: nodes.into_iter(),
+        }
+    }
+}
+
+impl<T> Iterator for IntoValues<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node_ref = self.nodes.next()?;
+
+        let Ok(node) = Rc::try_unwrap(node_ref) else {
+            panic!("The node is still referenced outside the graph");
+        };
+
+        Some(node.into_inner().into_value())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.nodes.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoValues<T> {}